@@ -0,0 +1,43 @@
+// Command line flags shared by the games.
+
+// The value of `--name <value>` or `--name=<value>`
+pub fn arg_value(args: impl IntoIterator<Item = String>, name: &str) -> Option<String> {
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        match arg.strip_prefix(name) {
+            Some("") => return args.next(),
+            Some(rest) => {
+                if let Some(value) = rest.strip_prefix('=') {
+                    return Some(value.to_string());
+                }
+            }
+            None => {}
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn value_follows_the_flag() {
+        assert_eq!(
+            arg_value(args("--replay run.replay"), "--replay"),
+            Some("run.replay".into())
+        );
+        assert_eq!(
+            arg_value(args("-v --replay=a b"), "--replay"),
+            Some("a".into())
+        );
+        assert_eq!(arg_value(args("--replay"), "--replay"), None);
+        assert_eq!(arg_value(args("--replays x"), "--replay"), None);
+    }
+}
@@ -8,15 +8,26 @@ use bevy_rapier2d::prelude::*;
 mod bundles;
 mod components;
 mod constants;
-mod events;
 mod resources;
+mod states;
 mod systems;
+#[cfg(test)]
+mod tests;
 
+pub use bevy_sandbox::collision_sound::*;
+pub use bevy_sandbox::config::*;
+pub use bevy_sandbox::cooldown::*;
+pub use bevy_sandbox::input::*;
+pub use bevy_sandbox::replay::*;
+pub use bevy_sandbox::rng::*;
+pub use bevy_sandbox::scoreboard::*;
+pub use bevy_sandbox::screen::*;
+pub use bevy_sandbox::settings::*;
 pub use bundles::*;
 pub use components::*;
 pub use constants::*;
-pub use events::*;
 pub use resources::*;
+pub use states::*;
 pub use systems::*;
 
 fn main() {
@@ -31,7 +42,7 @@ fn main() {
     };
     App::new()
         .add_plugins(DefaultPlugins.set(window_plugin))
-        .add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0))
+        .add_plugins(AsteroidsPlugin)
         // The stepping plugin is optional and can be used to control the game's update rate
         // .add_plugins(
         //     stepping::SteppingPlugin::default()
@@ -39,28 +50,141 @@ fn main() {
         //         .add_schedule(FixedUpdate)
         //         .at(Val::Percent(35.0), Val::Percent(50.0)),
         // )
-        .insert_resource(ClearColor(BACKGROUND_COLOR))
-        .insert_resource(BallCooldown::default())
-        .add_event::<BallCollisionEvent>()
-        .add_systems(Startup, setup)
-        .add_systems(
-            FixedUpdate,
-            (
-                calculate_player_orientation,
-                spawn_attacks,
-                spawn_balls,
-                calculate_acceleration,
-                calculate_player_velocity,
-                calculate_ball_velocity,
-                apply_velocity,
-                window_collision,
-                check_for_ball_collisions,
-                check_for_bullet_collisions,
-                despawn,
-                play_collision_sound,
-                update_scoreboard,
-            )
-                .chain(),
-        )
         .run();
 }
+
+// Ships, balls and waves for any number of local players, with Rapier running the physics
+pub struct AsteroidsPlugin;
+
+impl Plugin for AsteroidsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // Physics run in the same fixed timestep as the gameplay, so pausing the game pauses them too
+            .insert_resource(TimestepMode::Fixed {
+                dt: Time::<Fixed>::default().timestep().as_secs_f32(),
+                substeps: 1,
+            })
+            .add_plugins(ActionInputPlugin {
+                path: INPUT_BINDINGS_PATH,
+            })
+            .add_plugins(ConfigPlugin::<AsteroidsConfig>::new(CONFIG_PATH))
+            .add_plugins(RngPlugin)
+            // Inputs are recorded and played back by gameplay tick
+            .add_plugins(ReplayPlugin::new(FixedUpdate))
+            .add_plugins(CollisionSoundPlugin {
+                path: COLLISION_SOUND_PATH,
+            })
+            .add_plugins(ScoreboardPlugin {
+                style: ScoreboardStyle {
+                    font_size: SCOREBOARD_FONT_SIZE,
+                    padding: SCOREBOARD_TEXT_PADDING,
+                    text_color: TEXT_COLOR,
+                    value_color: SCORE_COLOR,
+                },
+            })
+            .add_plugins(ScreenPlugin {
+                style: ScreenStyle {
+                    title_font_size: TITLE_FONT_SIZE,
+                    line_font_size: SUBTITLE_FONT_SIZE,
+                    row_gap: SCREEN_ROW_GAP,
+                    overlay_color: SCREEN_OVERLAY_COLOR,
+                    title_color: TITLE_COLOR,
+                    text_color: TEXT_COLOR,
+                },
+            })
+            .add_plugins(SettingsPlugin {
+                state: GameState::Settings,
+                controls: SETTINGS_CONTROLS,
+            })
+            // Cooldowns tick with the gameplay, so they stop while the game is paused
+            .add_plugins((
+                CooldownPlugin::<BulletCooldown>::new(FixedUpdate),
+                CooldownPlugin::<InvulnerableCooldown>::new(FixedUpdate),
+                CooldownPlugin::<DespawnCooldown>::new(FixedUpdate),
+            ))
+            .add_plugins(
+                RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(PIXELS_PER_METER)
+                    .in_fixed_schedule(),
+            )
+            .init_resource::<WaveDirector>()
+            .init_resource::<FinalScore>()
+            .init_state::<GameState>()
+            .add_computed_state::<InGame>()
+            .enable_state_scoped_entities::<InGame>()
+            .add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (restyle_screens, restyle_hud, restyle_pieces)
+                    .chain()
+                    .run_if(resource_changed::<AsteroidsConfig>),
+            )
+            // Screens
+            .add_systems(
+                OnEnter(GameState::Menu),
+                (spawn_menu_screen, start_replay.run_if(replaying)),
+            )
+            .add_systems(OnEnter(GameState::Paused), (spawn_pause_screen, pause_time))
+            .add_systems(OnExit(GameState::Paused), unpause_time)
+            .add_systems(OnEnter(GameState::GameOver), spawn_game_over_screen)
+            .add_systems(OnEnter(InGame), setup_game)
+            .add_systems(OnExit(InGame), save_recording)
+            .add_systems(
+                Update,
+                (
+                    menu_input.run_if(in_state(GameState::Menu)),
+                    (toggle_pause, handle_gamepad_connections).run_if(in_state(InGame)),
+                    game_over_input.run_if(in_state(GameState::GameOver)),
+                ),
+            )
+            .configure_sets(
+                FixedUpdate,
+                (ReplaySet::Feed, ReplaySet::Release).run_if(in_state(GameState::Playing)),
+            )
+            // Input and steering set the velocities that the physics step integrates
+            .add_systems(
+                FixedUpdate,
+                (
+                    cycle_aim_mode,
+                    calculate_player_orientation,
+                    update_laser_energy,
+                    spawn_attacks,
+                    spawn_balls,
+                    calculate_acceleration,
+                    calculate_player_velocity,
+                    calculate_ball_velocity,
+                )
+                    .chain()
+                    .after(TickCooldowns)
+                    .after(ReplaySet::Feed)
+                    .before(PhysicsSet::SyncBackend)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            // Hits are read from the collision events of the physics step
+            .add_systems(
+                FixedUpdate,
+                (
+                    window_collision,
+                    spawn_wrap_ghosts,
+                    update_wrap_ghosts,
+                    check_for_bullet_collisions,
+                    anchor_lasers,
+                    check_for_laser_collisions,
+                    check_for_ball_collisions,
+                    limit_travel_distance,
+                    respawn_players,
+                    update_invulnerability,
+                    despawn,
+                    update_scoreboard,
+                    update_lives_ui,
+                    update_wave_ui,
+                    update_laser_ui,
+                    update_aim_ui,
+                    check_game_over,
+                )
+                    .chain()
+                    .after(PhysicsSet::Writeback)
+                    .before(ReplaySet::Release)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
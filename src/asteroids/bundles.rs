@@ -1,7 +1,11 @@
 use super::components::*;
 use super::constants::*;
+use super::resources::AsteroidsConfig;
+use super::states::*;
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
+use bevy_sandbox::collision::Shape;
+use bevy_sandbox::input::InputSource;
 
 #[derive(Bundle)]
 pub struct PlayerBundle {
@@ -10,39 +14,70 @@ pub struct PlayerBundle {
     pub material: MeshMaterial2d<ColorMaterial>,
     pub transform: Transform,
     pub player: Player,
+    pub slot: PlayerSlot,
+    pub controlled_by: ControlledBy,
+    pub aim_mode: AimMode,
     pub bullet_cooldown: BulletCooldown,
-    pub laser_cooldown: LaserCooldown,
+    pub laser_energy: LaserEnergy,
+    pub rigid_body: RigidBody,
     pub collider: Collider,
+    pub collision_groups: CollisionGroups,
+    pub active_events: ActiveEvents,
+    pub active_collision_types: ActiveCollisionTypes,
     pub velocity: Velocity,
     pub acceleration: Acceleration,
     pub score: Score,
+    pub lives: Lives,
+    pub edge_behaviour: EdgeBehaviour,
+    pub state_scoped: StateScoped<InGame>,
 }
 
 impl PlayerBundle {
+    pub fn rectangle(config: &AsteroidsConfig) -> Rectangle {
+        Rectangle {
+            half_size: config.player_size / 2.0,
+        }
+    }
+
     pub fn new(
         meshes: &mut ResMut<Assets<Mesh>>,
         materials: &mut ResMut<Assets<ColorMaterial>>,
+        config: &AsteroidsConfig,
+        slot: PlayerSlot,
+        source: InputSource,
     ) -> Self {
-        let half_size = PLAYER_SIZE / 2.0;
-
-        let rectangle = Rectangle { half_size };
+        let rectangle = Self::rectangle(config);
+        let shape = Shape::Rectangle(rectangle);
 
         Self {
             mesh: Mesh2d(meshes.add(rectangle)),
-            shape: Shape::Rectangle(rectangle),
-            material: MeshMaterial2d(materials.add(PLAYER_COLOR)),
+            collider: shape.collider(),
+            shape,
+            material: MeshMaterial2d(materials.add(slot.color(config))),
             transform: Transform {
-                translation: PLAYER_STARTING_POSITION.extend(0.0),
+                translation: slot.spawn_point(config).extend(0.0),
                 rotation: Quat::from_rotation_z(PLAYER_STARTING_ORIENTATION.angle_to(Vec2::X)),
                 ..default()
             },
             player: Player,
+            slot,
+            controlled_by: ControlledBy { a: source },
+            aim_mode: AimMode::available(source)[0],
             bullet_cooldown: BulletCooldown::default(),
-            laser_cooldown: LaserCooldown::default(),
-            collider: Collider::cuboid(half_size.x, half_size.y),
+            laser_energy: LaserEnergy::full(config),
+            rigid_body: RigidBody::KinematicVelocityBased,
+            collision_groups: CollisionGroups::new(PLAYER_GROUP, PLAYER_FILTER),
+            active_events: ActiveEvents::COLLISION_EVENTS,
+            active_collision_types: ActiveCollisionTypes::default()
+                | ActiveCollisionTypes::KINEMATIC_KINEMATIC,
             velocity: Velocity::default(),
             acceleration: Acceleration::default(),
             score: Score::default(),
+            lives: Lives {
+                a: config.player_starting_lives,
+            },
+            edge_behaviour: EdgeBehaviour::Wrap,
+            state_scoped: StateScoped(InGame),
         }
     }
 }
@@ -54,29 +89,59 @@ pub struct BallBundle {
     pub material: MeshMaterial2d<ColorMaterial>,
     pub transform: Transform,
     pub ball: Ball,
+    pub ball_size: BallSize,
+    pub speed: Speed,
+    pub behaviour: BallBehaviour,
+    pub edge_behaviour: EdgeBehaviour,
+    pub rigid_body: RigidBody,
+    pub collider: Collider,
+    pub sensor: Sensor,
+    pub collision_groups: CollisionGroups,
+    pub active_events: ActiveEvents,
+    pub active_collision_types: ActiveCollisionTypes,
     pub velocity: Velocity,
+    pub state_scoped: StateScoped<InGame>,
 }
 
 impl BallBundle {
     pub fn new(
         meshes: &mut ResMut<Assets<Mesh>>,
         materials: &mut ResMut<Assets<ColorMaterial>>,
+        config: &AsteroidsConfig,
         starting_position: Vec2,
+        ball_size: BallSize,
+        starting_velocity: Vec2,
+        speed: f32,
+        behaviour: BallBehaviour,
     ) -> Self {
+        // The mesh and the collider are built from the same circle, so what you see is what gets hit
         let circle = Circle {
-            radius: BALL_DIAMETER / 2.0,
+            radius: ball_size.radius(config),
         };
+        let shape = Shape::Circle(circle);
 
         Self {
             mesh: Mesh2d(meshes.add(circle)),
-            shape: Shape::Circle(circle),
-            material: MeshMaterial2d(materials.add(BALL_COLOR)),
+            collider: shape.collider(),
+            shape,
+            material: MeshMaterial2d(materials.add(config.ball_color)),
             transform: Transform::from_translation(starting_position.extend(0.0)),
             ball: Ball,
+            ball_size,
+            speed: Speed { a: speed },
+            behaviour,
+            edge_behaviour: EdgeBehaviour::Wrap,
+            rigid_body: RigidBody::KinematicVelocityBased,
+            sensor: Sensor,
+            collision_groups: CollisionGroups::new(BALL_GROUP, BALL_FILTER),
+            active_events: ActiveEvents::COLLISION_EVENTS,
+            active_collision_types: ActiveCollisionTypes::default()
+                | ActiveCollisionTypes::KINEMATIC_KINEMATIC,
             velocity: Velocity {
-                linvel: Vec2::ZERO,
+                linvel: starting_velocity,
                 ..default()
             },
+            state_scoped: StateScoped(InGame),
         }
     }
 }
@@ -88,35 +153,64 @@ pub struct BulletBundle {
     pub material: MeshMaterial2d<ColorMaterial>,
     pub transform: Transform,
     pub bullet: Bullet,
+    pub rigid_body: RigidBody,
+    pub collider: Collider,
+    pub sensor: Sensor,
+    pub collision_groups: CollisionGroups,
+    pub active_events: ActiveEvents,
+    pub active_collision_types: ActiveCollisionTypes,
     pub velocity: Velocity,
+    pub generated_by: GeneratedBy,
+    pub edge_behaviour: EdgeBehaviour,
+    pub travel_distance: TravelDistance,
+    pub state_scoped: StateScoped<InGame>,
 }
 
 impl BulletBundle {
+    pub fn annulus(config: &AsteroidsConfig) -> Annulus {
+        Annulus {
+            inner_circle: Circle {
+                radius: config.bullet_radius / 2.0,
+            },
+            outer_circle: Circle {
+                radius: config.bullet_radius,
+            },
+        }
+    }
+
     pub fn new(
         meshes: &mut ResMut<Assets<Mesh>>,
         materials: &mut ResMut<Assets<ColorMaterial>>,
+        config: &AsteroidsConfig,
         starting_transform: Transform,
         starting_velocity: Vec3,
+        max_travel: f32,
+        player_entity: Entity,
     ) -> Self {
-        let annulus = Annulus {
-            inner_circle: Circle {
-                radius: BULLET_RADIUS / 2.0,
-            },
-            outer_circle: Circle {
-                radius: BULLET_RADIUS,
-            },
-        };
+        let annulus = Self::annulus(config);
+        let shape = Shape::Annulus(annulus);
 
         Self {
             mesh: Mesh2d(meshes.add(annulus)),
-            shape: Shape::Annulus(annulus),
-            material: MeshMaterial2d(materials.add(BULLET_COLOR)),
+            collider: shape.collider(),
+            shape,
+            material: MeshMaterial2d(materials.add(config.bullet_color)),
             transform: starting_transform,
             bullet: Bullet,
+            rigid_body: RigidBody::KinematicVelocityBased,
+            sensor: Sensor,
+            collision_groups: CollisionGroups::new(BULLET_GROUP, BULLET_FILTER),
+            active_events: ActiveEvents::COLLISION_EVENTS,
+            active_collision_types: ActiveCollisionTypes::default()
+                | ActiveCollisionTypes::KINEMATIC_KINEMATIC,
             velocity: Velocity {
                 linvel: starting_velocity.truncate(),
                 ..default()
             },
+            generated_by: GeneratedBy(player_entity),
+            edge_behaviour: EdgeBehaviour::Wrap,
+            travel_distance: TravelDistance { a: max_travel },
+            state_scoped: StateScoped(InGame),
         }
     }
 }
@@ -127,31 +221,48 @@ pub struct LaserBundle {
     pub shape: Shape,
     pub material: MeshMaterial2d<ColorMaterial>,
     pub transform: Transform,
-    pub bullet: Laser,
-    pub despawn_timer: DespawnCooldown,
+    pub laser: Laser,
+    // The beam is not a physics body, it hits with a ray cast along its length
+    pub anchor_to: AnchorTo,
+    pub generated_by: GeneratedBy,
 }
 
 impl LaserBundle {
+    pub fn rectangle(config: &AsteroidsConfig) -> Rectangle {
+        Rectangle {
+            half_size: Vec2::new(config.laser_length / 2.0, config.laser_width / 2.0),
+        }
+    }
+
+    // The beam starts at the player and points forward
+    pub fn offset(config: &AsteroidsConfig) -> Vec3 {
+        Vec3::X * config.laser_length / 2.0
+    }
+
     pub fn new(
         meshes: &mut ResMut<Assets<Mesh>>,
         materials: &mut ResMut<Assets<ColorMaterial>>,
+        config: &AsteroidsConfig,
         player_transform: Transform,
+        player_entity: Entity,
     ) -> Self {
-        let transform = player_transform
-            .with_translation(player_transform.rotation.mul_vec3(Vec3::X) * LASER_LENGTH / 2.0)
-            .with_rotation(Quat::default());
-
-        let rectangle = Rectangle {
-            half_size: Vec2::new(LASER_LENGTH / 2.0, LASER_WIDTH / 2.0),
+        let rectangle = Self::rectangle(config);
+        let anchor_to = AnchorTo {
+            target: player_entity,
+            offset: Self::offset(config),
         };
 
+        let mut transform = Transform::default();
+        anchor_to.anchor(&mut transform, &player_transform);
+
         Self {
             mesh: Mesh2d(meshes.add(rectangle)),
             shape: Shape::Rectangle(rectangle),
-            material: MeshMaterial2d(materials.add(LASER_COLOR)),
+            material: MeshMaterial2d(materials.add(config.laser_color)),
             transform,
-            bullet: Laser,
-            despawn_timer: DespawnCooldown::new(LASER_LIFE),
+            laser: Laser,
+            anchor_to,
+            generated_by: GeneratedBy(player_entity),
         }
     }
 }
@@ -1,17 +1,31 @@
+pub mod aim_mode;
+pub mod ball_behaviour;
+pub mod ball_size;
 pub mod bullet;
+pub mod controlled_by;
+pub mod edge_behaviour;
 pub mod entity_tags;
 pub mod laser;
+pub mod laser_energy;
+pub mod lives;
 pub mod movement;
+pub mod player_slot;
 pub mod relationships;
 pub mod score;
-pub mod shape;
 pub mod timers;
 
+pub use aim_mode::*;
+pub use ball_behaviour::*;
+pub use ball_size::*;
 pub use bullet::*;
+pub use controlled_by::*;
+pub use edge_behaviour::*;
 pub use entity_tags::*;
 pub use laser::*;
+pub use laser_energy::*;
+pub use lives::*;
 pub use movement::*;
+pub use player_slot::*;
 pub use relationships::*;
 pub use score::*;
-pub use shape::*;
 pub use timers::*;
@@ -0,0 +1,54 @@
+use bevy::prelude::Component;
+use bevy_sandbox::input::{AxisAction, InputSource};
+
+// How a player points their ship
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AimMode {
+    // The aim stick, and the thrust stick or keys to move
+    Stick,
+    // Towards the mouse cursor, and the thrust keys to move
+    Mouse,
+    // The twin-stick keys, one set to move and one to aim
+    TwinStick,
+}
+
+impl AimMode {
+    // The modes that make sense for an input device, the first one is the default
+    pub fn available(source: InputSource) -> &'static [AimMode] {
+        match source {
+            InputSource::Keyboard => &[AimMode::Mouse, AimMode::TwinStick],
+            InputSource::Gamepad(_) => &[AimMode::Stick],
+        }
+    }
+
+    // The next mode available for the device, wrapping around
+    pub fn next(&self, source: InputSource) -> AimMode {
+        let modes = AimMode::available(source);
+        let index = modes.iter().position(|mode| mode == self).unwrap_or(0);
+        modes[(index + 1) % modes.len()]
+    }
+
+    pub fn thrust_action(&self) -> AxisAction {
+        match self {
+            AimMode::Stick | AimMode::Mouse => AxisAction::Thrust,
+            AimMode::TwinStick => AxisAction::TwinStickThrust,
+        }
+    }
+
+    // The axis to aim with, the mouse has none
+    pub fn aim_action(&self) -> Option<AxisAction> {
+        match self {
+            AimMode::Stick => Some(AxisAction::Aim),
+            AimMode::Mouse => None,
+            AimMode::TwinStick => Some(AxisAction::TwinStickAim),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AimMode::Stick => "Stick",
+            AimMode::Mouse => "Mouse",
+            AimMode::TwinStick => "Twin-stick",
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use bevy::math::Vec2;
+use bevy::prelude::Component;
+
+// How a ball moves around the players. Turn rates are in radians per second.
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub enum BallBehaviour {
+    // Keeps going in a straight line
+    Drift,
+    // Turns towards the nearest player
+    Seek { turn_rate: f32 },
+    // Circles around the nearest player at `radius`
+    Orbit { radius: f32, turn_rate: f32 },
+    // Turns away from the nearest player
+    Flee { turn_rate: f32 },
+}
+
+impl BallBehaviour {
+    // Returns the direction the ball wants to head in, and how fast it may turn towards it.
+    // Without a player to react to, every ball drifts.
+    pub fn steering(
+        &self,
+        ball_position: Vec2,
+        nearest_player: Option<Vec2>,
+    ) -> Option<(Vec2, f32)> {
+        let to_player = nearest_player? - ball_position;
+        let towards = to_player.try_normalize()?;
+
+        match *self {
+            BallBehaviour::Drift => None,
+            BallBehaviour::Seek { turn_rate } => Some((towards, turn_rate)),
+            BallBehaviour::Flee { turn_rate } => Some((-towards, turn_rate)),
+            BallBehaviour::Orbit { radius, turn_rate } => {
+                // Follow the tangent, leaning in or out to get back on the circle
+                let correction = ((to_player.length() - radius) / radius).clamp(-1.0, 1.0);
+                let heading = (towards.perp() + towards * correction).normalize();
+                Some((heading, turn_rate))
+            }
+        }
+    }
+}
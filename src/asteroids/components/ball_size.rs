@@ -0,0 +1,40 @@
+use super::super::resources::AsteroidsConfig;
+use bevy::prelude::Component;
+
+// Size tier of a ball. Hitting a ball splits it into two balls of the next tier,
+// until the smallest tier, which is simply destroyed.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum BallSize {
+    #[default]
+    Large,
+    Medium,
+    Small,
+}
+
+impl BallSize {
+    pub fn radius(&self, config: &AsteroidsConfig) -> f32 {
+        match self {
+            BallSize::Large => config.ball_large_diameter / 2.0,
+            BallSize::Medium => config.ball_medium_diameter / 2.0,
+            BallSize::Small => config.ball_small_diameter / 2.0,
+        }
+    }
+
+    // Points awarded for destroying a ball of this size, smaller balls are harder to hit
+    pub fn score(&self, config: &AsteroidsConfig) -> usize {
+        match self {
+            BallSize::Large => config.ball_large_score,
+            BallSize::Medium => config.ball_medium_score,
+            BallSize::Small => config.ball_small_score,
+        }
+    }
+
+    // The tier of the balls spawned when this one is hit, if any
+    pub fn split(&self) -> Option<BallSize> {
+        match self {
+            BallSize::Large => Some(BallSize::Medium),
+            BallSize::Medium => Some(BallSize::Small),
+            BallSize::Small => None,
+        }
+    }
+}
@@ -0,0 +1,8 @@
+use bevy::prelude::Component;
+use bevy_sandbox::input::InputSource;
+
+// The device that steers and fires for this player
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ControlledBy {
+    pub a: InputSource,
+}
@@ -0,0 +1,22 @@
+use bevy::math::BVec2;
+use bevy::prelude::Component;
+
+// What happens to an entity when it reaches the edge of the window
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EdgeBehaviour {
+    // Stays inside the window and stops
+    Clamp,
+    // Leaves through one side and comes back through the opposite one
+    Wrap,
+    // Is despawned once it's fully outside the window
+    Despawn,
+    // Stays inside the window and bounces back
+    Bounce,
+}
+
+// Copy of the mesh of a wrapping entity, drawn on the opposite side of the window
+// while the entity straddles an edge. `axes` tells which edges it mirrors.
+#[derive(Component)]
+pub struct WrapGhost {
+    pub axes: BVec2,
+}
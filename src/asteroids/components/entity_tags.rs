@@ -1,7 +1,11 @@
 pub mod ball;
+pub mod entering_window;
 pub mod player;
-pub mod score_board_ui;
+pub mod respawning;
+pub mod wave_ui;
 
 pub use ball::*;
+pub use entering_window::*;
 pub use player::*;
-pub use score_board_ui::*;
+pub use respawning::*;
+pub use wave_ui::*;
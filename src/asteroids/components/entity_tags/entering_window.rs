@@ -0,0 +1,6 @@
+use bevy::prelude::Component;
+
+// The entity was spawned outside the window and is flying in, the edges ignore it
+// until its centre is inside
+#[derive(Component)]
+pub struct EnteringWindow;
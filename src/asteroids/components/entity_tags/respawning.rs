@@ -0,0 +1,5 @@
+use bevy::prelude::Component;
+
+// The player lost a life and is waiting for the centre of the field to be clear
+#[derive(Component)]
+pub struct Respawning;
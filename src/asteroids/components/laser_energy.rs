@@ -0,0 +1,39 @@
+use super::super::resources::AsteroidsConfig;
+use bevy::prelude::Component;
+
+// Energy of the laser beam. Firing drains it, and emptying it overheats the laser,
+// which can't be fired again until it has recharged enough.
+#[derive(Component)]
+pub struct LaserEnergy {
+    pub a: f32,
+    pub overheated: bool,
+}
+
+impl LaserEnergy {
+    pub fn full(config: &AsteroidsConfig) -> Self {
+        LaserEnergy {
+            a: config.laser_energy_max,
+            overheated: false,
+        }
+    }
+
+    pub fn drain(&mut self, amount: f32) {
+        self.a = (self.a - amount).max(0.0);
+
+        if self.a == 0.0 {
+            self.overheated = true;
+        }
+    }
+
+    pub fn recharge(&mut self, amount: f32, config: &AsteroidsConfig) {
+        self.a = (self.a + amount).min(config.laser_energy_max);
+
+        if self.a >= config.laser_overheat_recovery {
+            self.overheated = false;
+        }
+    }
+
+    pub fn can_fire(&self) -> bool {
+        !self.overheated
+    }
+}
@@ -0,0 +1,15 @@
+use super::super::constants::PLAYER_STARTING_LIVES;
+use bevy::prelude::Component;
+
+#[derive(Component)]
+pub struct Lives {
+    pub a: u32,
+}
+
+impl Default for Lives {
+    fn default() -> Self {
+        Lives {
+            a: PLAYER_STARTING_LIVES,
+        }
+    }
+}
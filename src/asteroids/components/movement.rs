@@ -1,5 +1,9 @@
 pub mod acceleration;
 pub mod is_braking;
+pub mod speed;
+pub mod travel_distance;
 
 pub use acceleration::*;
 pub use is_braking::*;
+pub use speed::*;
+pub use travel_distance::*;
@@ -0,0 +1,7 @@
+use bevy::prelude::{Component, Deref, DerefMut};
+
+// Top speed of a ball, set by the wave that spawned it
+#[derive(Component, Deref, DerefMut)]
+pub struct Speed {
+    pub a: f32,
+}
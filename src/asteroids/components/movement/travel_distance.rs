@@ -0,0 +1,7 @@
+use bevy::prelude::{Component, Deref, DerefMut};
+
+// Distance the entity can still travel before it's despawned
+#[derive(Component, Deref, DerefMut)]
+pub struct TravelDistance {
+    pub a: f32,
+}
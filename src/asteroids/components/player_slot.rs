@@ -0,0 +1,25 @@
+use super::super::resources::AsteroidsConfig;
+use bevy::color::Color;
+use bevy::math::Vec2;
+use bevy::prelude::Component;
+
+// Seat of a player in a local game. The keyboard always sits in the first one,
+// and gamepads take the free ones as they join.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PlayerSlot {
+    pub a: usize,
+}
+
+impl PlayerSlot {
+    pub fn color(&self, config: &AsteroidsConfig) -> Color {
+        config.player_colors[self.a % config.player_colors.len()]
+    }
+
+    pub fn spawn_point(&self, config: &AsteroidsConfig) -> Vec2 {
+        config.player_spawn_points[self.a % config.player_spawn_points.len()]
+    }
+
+    pub fn name(&self) -> String {
+        format!("Player {}", self.a + 1)
+    }
+}
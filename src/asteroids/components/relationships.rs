@@ -1,5 +1,6 @@
 use bevy::ecs::component::Component;
 use bevy::ecs::entity::Entity;
+use bevy::math::Vec3;
 use bevy::transform::components::Transform;
 
 /// The entity that generated this entity
@@ -9,7 +10,7 @@ use bevy::transform::components::Transform;
 /// and can be modified directly to change the target.
 #[derive(Component)]
 #[relationship(relationship_target = Generated)]
-pub struct GeneratedBy(Entity);
+pub struct GeneratedBy(pub Entity);
 
 /// All entities that are targeting this entity.
 ///
@@ -24,17 +25,38 @@ pub struct Generated(Vec<Entity>);
 // For the lasers, so we move them with the player
 #[derive(Component)]
 #[relationship(relationship_target = Anchored)]
-pub struct AnchorTo(Entity);
+pub struct AnchorTo {
+    #[relationship]
+    pub target: Entity,
+    // Position relative to the target, in the target's local space
+    pub offset: Vec3,
+}
 
 impl AnchorTo {
     pub fn anchor(&self, anchored_transform: &mut Transform, new_transform: &Transform) {
         // Move the anchored entity to the player's position
-        anchored_transform.translation = new_transform.translation;
+        anchored_transform.translation =
+            new_transform.translation + new_transform.rotation.mul_vec3(self.offset);
         // Set the rotation to match the player's rotation
         anchored_transform.rotation = new_transform.rotation;
     }
 }
 
+/// All entities anchored to this entity.
+///
+/// Anchored entities are despawned along with this entity.
 #[derive(Component, Debug)]
-#[relationship_target(relationship = AnchorTo)]
+#[relationship_target(relationship = AnchorTo, linked_spawn)]
 pub struct Anchored(Vec<Entity>);
+
+// For the HUD lines, so each player has its own
+#[derive(Component)]
+#[relationship(relationship_target = Hud)]
+pub struct HudOf(pub Entity);
+
+/// The HUD lines showing this entity.
+///
+/// They are despawned along with this entity.
+#[derive(Component, Debug)]
+#[relationship_target(relationship = HudOf, linked_spawn)]
+pub struct Hud(Vec<Entity>);
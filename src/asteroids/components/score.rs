@@ -1,12 +1,6 @@
 use bevy::prelude::Component;
 
-#[derive(Component)]
+#[derive(Component, Default)]
 pub struct Score {
     pub a: usize,
 }
-
-impl Default for Score {
-    fn default() -> Self {
-        Score { a: 0 }
-    }
-}
@@ -1,7 +1,7 @@
 pub mod bullet_cooldown;
-pub mod laser_cooldown;
+pub mod invulnerable_cooldown;
 pub mod laser_life_cooldown;
 
 pub use bullet_cooldown::*;
-pub use laser_cooldown::*;
+pub use invulnerable_cooldown::*;
 pub use laser_life_cooldown::*;
@@ -4,6 +4,7 @@ use bevy::prelude::Deref;
 use bevy::prelude::DerefMut;
 use bevy::prelude::Timer;
 use bevy::time::TimerMode;
+use bevy_sandbox::cooldown::Cooldown;
 
 #[derive(Component, Deref, DerefMut)]
 pub struct BulletCooldown {
@@ -17,3 +18,9 @@ impl Default for BulletCooldown {
         }
     }
 }
+
+impl Cooldown for BulletCooldown {
+    fn timer_mut(&mut self) -> &mut Timer {
+        &mut self.a
+    }
+}
@@ -0,0 +1,26 @@
+use bevy::prelude::Component;
+use bevy::prelude::Deref;
+use bevy::prelude::DerefMut;
+use bevy::prelude::Timer;
+use bevy::time::TimerMode;
+use bevy_sandbox::cooldown::Cooldown;
+
+// While present, balls can't hurt the player. It is removed once the timer finishes.
+#[derive(Component, Deref, DerefMut)]
+pub struct InvulnerableCooldown {
+    pub a: Timer,
+}
+
+impl InvulnerableCooldown {
+    pub fn new(seconds: f32) -> Self {
+        InvulnerableCooldown {
+            a: Timer::from_seconds(seconds, TimerMode::Once),
+        }
+    }
+}
+
+impl Cooldown for InvulnerableCooldown {
+    fn timer_mut(&mut self) -> &mut Timer {
+        &mut self.a
+    }
+}
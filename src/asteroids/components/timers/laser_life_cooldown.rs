@@ -3,6 +3,7 @@ use bevy::prelude::Deref;
 use bevy::prelude::DerefMut;
 use bevy::prelude::Timer;
 use bevy::time::TimerMode;
+use bevy_sandbox::cooldown::Cooldown;
 
 #[derive(Component, Deref, DerefMut)]
 pub struct DespawnCooldown {
@@ -16,3 +17,9 @@ impl DespawnCooldown {
         }
     }
 }
+
+impl Cooldown for DespawnCooldown {
+    fn timer_mut(&mut self) -> &mut Timer {
+        &mut self.a
+    }
+}
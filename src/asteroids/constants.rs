@@ -1,59 +1,125 @@
-use bevy::{
-    color::Color,
-    input::{gamepad::GamepadButton, keyboard::KeyCode},
-    prelude::Vec2,
-    ui::Val,
-};
+use bevy::{color::Color, prelude::Vec2, ui::Val};
+use bevy_rapier2d::geometry::Group;
+use bevy_sandbox::input::{AxisAction, ButtonAction};
+use bevy_sandbox::settings::Control;
+
+// * CONFIG *
+pub const CONFIG_PATH: &str = "config/asteroids.ron"; // Inside the assets folder
 
 // * PLAYER *
 // These constants are defined in `Transform` units.
 // Using the default 2D camera they correspond 1:1 with screen pixels.
 pub const PLAYER_SIZE: Vec2 = Vec2::new(20.0, 20.0);
-pub const PLAYER_STARTING_POSITION: Vec2 = Vec2::new(0.0, 0.0);
+// Where each player slot starts and respawns
+pub const PLAYER_SPAWN_POINTS: [Vec2; 4] = [
+    Vec2::new(-150.0, 0.0),
+    Vec2::new(150.0, 0.0),
+    Vec2::new(0.0, 150.0),
+    Vec2::new(0.0, -150.0),
+];
 pub const PLAYER_STARTING_ORIENTATION: Vec2 = Vec2::new(0.0, 1.0);
 pub const PLAYER_STARTING_VELOCITY: Vec2 = Vec2::new(0.0, 0.0);
 pub const PLAYER_SPEED: f32 = 500.0;
 pub const PLAYER_STARTING_ACCELERATION: Vec2 = Vec2::new(0.0, 0.0);
 pub const PLAYER_ACCELERATION: f32 = 50000.0;
 pub const PLAYER_BRAKING_MULTIPLIER: f32 = 2.0;
+pub const PLAYER_STARTING_LIVES: u32 = 3;
+pub const PLAYER_INVULNERABLE_TIME: f32 = 2.0; // Time in seconds the player can't be hurt after respawning
+pub const PLAYER_INVULNERABLE_BLINK_TIME: f32 = 0.1; // Time in seconds between visibility toggles while invulnerable
+pub const PLAYER_SAFE_SPAWN_RADIUS: f32 = 150.0; // No ball may be this close to the spawn point when respawning
 
 // * BALL *
-pub const BALL_DIAMETER: f32 = 30.0;
+pub const BALL_LARGE_DIAMETER: f32 = 60.0;
+pub const BALL_MEDIUM_DIAMETER: f32 = 30.0;
+pub const BALL_SMALL_DIAMETER: f32 = 15.0;
 pub const BALL_SPEED: f32 = 400.0;
-pub const BALL_COOLDOWN: f32 = 1.0; // Time in seconds before the next ball spawns
+// Points awarded to the player that destroys a ball of each size
+pub const BALL_LARGE_SCORE: usize = 20;
+pub const BALL_MEDIUM_SCORE: usize = 50;
+pub const BALL_SMALL_SCORE: usize = 100;
+pub const BALL_SPLIT_ANGLE: f32 = std::f32::consts::FRAC_PI_4; // Angle of each half from the hit ball's heading
+pub const BALL_TURN_RATE: f32 = std::f32::consts::PI; // Radians per second a seeking or fleeing ball turns
+pub const BALL_ORBIT_RADIUS: f32 = 250.0; // Distance from the player an orbiting ball circles at
+pub const BALL_ORBIT_TURN_RATE: f32 = std::f32::consts::TAU; // Radians per second an orbiting ball turns
+
+// * WAVE *
+pub const WAVE_SPAWN_COOLDOWN: f32 = 1.0; // Time in seconds between two balls of the same wave
+pub const WAVE_BREAK_TIME: f32 = 3.0; // Time in seconds between clearing a wave and the next one
+pub const WAVE_BASE_COUNT: u32 = 3; // Balls in the first wave
+pub const WAVE_COUNT_INCREMENT: u32 = 1; // Extra balls in each following wave
+pub const WAVE_BASE_SPEED: f32 = 200.0; // Top speed of the balls of the first wave
+pub const WAVE_SPEED_INCREMENT: f32 = 20.0; // Extra speed in each following wave, up to `BALL_SPEED`
 
 // * BULLET *
 pub const BULLET_RADIUS: f32 = 5.0;
 pub const BULLET_SPEED: f32 = 1500.0;
 pub const BULLET_COOLDOWN: f32 = 0.05; // Time in seconds before the next bullet can be fired
+pub const BULLET_MAX_TRAVEL: f32 = 1200.0; // Distance a bullet flies before it vanishes
 
 // * LASER *
 pub const LASER_LENGTH: f32 = 100.0;
 pub const LASER_WIDTH: f32 = 5.0;
-pub const LASER_COOLDOWN: f32 = 0.1; // Time in seconds before the next bullet can be fired
-pub const LASER_LIFE: f32 = 99999.0; // Time in seconds before the laser despawns
+pub const LASER_ENERGY_MAX: f32 = 100.0;
+pub const LASER_ENERGY_DRAIN: f32 = 50.0; // Energy spent per second while the beam is on
+pub const LASER_ENERGY_RECHARGE: f32 = 25.0; // Energy recovered per second while the beam is off
+pub const LASER_OVERHEAT_RECOVERY: f32 = 50.0; // Energy needed to fire again after overheating
+
+// * PHYSICS *
+pub const PIXELS_PER_METER: f32 = 100.0;
+// Every kind of entity is in its own group, and only looks for the groups it can hit
+pub const PLAYER_GROUP: Group = Group::GROUP_1;
+pub const BALL_GROUP: Group = Group::GROUP_2;
+pub const BULLET_GROUP: Group = Group::GROUP_3;
+pub const LASER_GROUP: Group = Group::GROUP_4;
+pub const PLAYER_FILTER: Group = BALL_GROUP;
+pub const BALL_FILTER: Group = PLAYER_GROUP.union(BULLET_GROUP).union(LASER_GROUP);
+pub const BULLET_FILTER: Group = BALL_GROUP;
+pub const LASER_FILTER: Group = BALL_GROUP;
+
+// * SOUND *
+pub const COLLISION_SOUND_PATH: &str = "sounds/breakout_collision.ogg";
 
 // * SCOREBOARD *
 pub const SCOREBOARD_FONT_SIZE: f32 = 33.0;
 pub const SCOREBOARD_TEXT_PADDING: Val = Val::Px(5.0);
 
+// * SCREENS *
+pub const TITLE_FONT_SIZE: f32 = 80.0;
+pub const SUBTITLE_FONT_SIZE: f32 = 33.0;
+pub const SCREEN_ROW_GAP: Val = Val::Px(20.0);
+
 // * COLORS *
 pub const BACKGROUND_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
-pub const PLAYER_COLOR: Color = Color::srgb(0.3, 0.3, 0.7);
+// Colour of each player slot
+pub const PLAYER_COLORS: [Color; 4] = [
+    Color::srgb(0.3, 0.3, 0.7),
+    Color::srgb(0.7, 0.3, 0.3),
+    Color::srgb(0.3, 0.6, 0.3),
+    Color::srgb(0.7, 0.5, 0.1),
+];
 pub const BALL_COLOR: Color = Color::srgb(1.0, 0.5, 0.5);
 pub const BULLET_COLOR: Color = Color::srgb(0.0, 0.0, 1.0);
 pub const LASER_COLOR: Color = Color::srgb(1.0, 0.0, 0.0);
-pub const BRICK_NORMAL_COLOR: Color = Color::srgb(0.5, 0.5, 1.0);
-pub const BRICK_SPEED_COLOR: Color = Color::srgb(0.5, 1.0, 0.5);
-pub const BRICK_EXTRA_BALL_COLOR: Color = Color::srgb(1.0, 1.0, 0.5);
-pub const WALL_COLOR: Color = Color::srgb(0.8, 0.8, 0.8);
+pub const LASER_OVERHEATED_COLOR: Color = Color::srgb(0.6, 0.6, 0.6);
 pub const TEXT_COLOR: Color = Color::srgb(0.5, 0.5, 1.0);
 pub const SCORE_COLOR: Color = Color::srgb(1.0, 0.5, 0.5);
+pub const TITLE_COLOR: Color = Color::srgb(0.3, 0.3, 0.7);
+pub const SCREEN_OVERLAY_COLOR: Color = Color::srgba(0.9, 0.9, 0.9, 0.7);
 
-// * KEYBOARD *
-pub const KEYBOARD_BULLET_BUTTON: KeyCode = KeyCode::Space;
-pub const KEYBOARD_LASER_BUTTON: KeyCode = KeyCode::ShiftLeft;
-
-// * GAMEPAD *
-pub const GAMEPAD_BULLET_BUTTON: GamepadButton = GamepadButton::RightTrigger;
-pub const GAMEPAD_LASER_BUTTON: GamepadButton = GamepadButton::RightTrigger2;
+// * INPUT *
+pub const INPUT_BINDINGS_PATH: &str = "config/asteroids_input.ron"; // Where rebound controls are saved
+// What the settings screen lets the player rebind. The twin-stick layout is keyboard only.
+pub const SETTINGS_CONTROLS: &[Control] = &[
+    Control::Keys(AxisAction::Thrust),
+    Control::Stick(AxisAction::Thrust),
+    Control::Stick(AxisAction::Aim),
+    Control::Keys(AxisAction::TwinStickThrust),
+    Control::Keys(AxisAction::TwinStickAim),
+    Control::Button(ButtonAction::FireBullet),
+    Control::Button(ButtonAction::FireLaser),
+    Control::Button(ButtonAction::CycleAimMode),
+    Control::Button(ButtonAction::Pause),
+    Control::Button(ButtonAction::Confirm),
+    Control::Button(ButtonAction::Back),
+    Control::Button(ButtonAction::Settings),
+];
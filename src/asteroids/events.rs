@@ -1,4 +0,0 @@
-use bevy::prelude::*;
-
-#[derive(Event, Default)]
-pub struct BallCollisionEvent;
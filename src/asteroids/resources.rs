@@ -1,24 +1,375 @@
-use super::constants::BALL_COOLDOWN;
+use super::components::{BallBehaviour, BallSize};
+use super::constants::*;
+use bevy::color::Color;
+use bevy::math::Vec2;
 use bevy::prelude::Deref;
 use bevy::prelude::DerefMut;
 use bevy::prelude::Timer;
+use bevy::reflect::TypePath;
 use bevy::time::TimerMode;
-use bevy::{asset::Handle, audio::AudioSource, prelude::Resource};
+use bevy::{asset::Asset, prelude::Resource};
+use bevy_sandbox::config::{Config, ConfigError};
+use bevy_sandbox::screen::TextColors;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
 
-#[derive(Resource, Deref)]
-pub struct CollisionSound {
-    pub a: Handle<AudioSource>,
+// Tuning of the ships, balls, waves and weapons, from `assets/config/asteroids.ron`
+#[derive(Asset, Resource, TypePath, Clone, PartialEq, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AsteroidsConfig {
+    // Seed of every run, instead of a fresh one. `--seed` wins over it.
+    pub seed: Option<u64>,
+    pub player_size: Vec2,
+    // Where each player slot starts and respawns
+    pub player_spawn_points: [Vec2; 4],
+    pub player_speed: f32,
+    pub player_acceleration: f32,
+    pub player_braking_multiplier: f32,
+    pub player_starting_lives: u32,
+    pub player_invulnerable_time: f32,
+    pub player_invulnerable_blink_time: f32,
+    pub player_safe_spawn_radius: f32,
+    pub ball_large_diameter: f32,
+    pub ball_medium_diameter: f32,
+    pub ball_small_diameter: f32,
+    pub ball_large_score: usize,
+    pub ball_medium_score: usize,
+    pub ball_small_score: usize,
+    pub ball_speed: f32,
+    pub ball_split_angle: f32,
+    pub ball_turn_rate: f32,
+    pub ball_orbit_radius: f32,
+    pub ball_orbit_turn_rate: f32,
+    pub wave_spawn_cooldown: f32,
+    pub wave_break_time: f32,
+    pub wave_base_count: u32,
+    pub wave_count_increment: u32,
+    pub wave_base_speed: f32,
+    pub wave_speed_increment: f32,
+    pub bullet_radius: f32,
+    pub bullet_speed: f32,
+    pub bullet_cooldown: f32,
+    pub bullet_max_travel: f32,
+    pub laser_length: f32,
+    pub laser_width: f32,
+    pub laser_energy_max: f32,
+    pub laser_energy_drain: f32,
+    pub laser_energy_recharge: f32,
+    pub laser_overheat_recovery: f32,
+    pub background_color: Color,
+    // Of each player slot
+    pub player_colors: [Color; 4],
+    pub ball_color: Color,
+    pub bullet_color: Color,
+    pub laser_color: Color,
+    pub laser_overheated_color: Color,
+    pub text_color: Color,
+    pub score_color: Color,
+    pub title_color: Color,
+    pub screen_overlay_color: Color,
 }
 
-#[derive(Resource, Deref, DerefMut)]
-pub struct BallCooldown {
-    pub a: Timer,
+impl Default for AsteroidsConfig {
+    fn default() -> Self {
+        AsteroidsConfig {
+            seed: None,
+            player_size: PLAYER_SIZE,
+            player_spawn_points: PLAYER_SPAWN_POINTS,
+            player_speed: PLAYER_SPEED,
+            player_acceleration: PLAYER_ACCELERATION,
+            player_braking_multiplier: PLAYER_BRAKING_MULTIPLIER,
+            player_starting_lives: PLAYER_STARTING_LIVES,
+            player_invulnerable_time: PLAYER_INVULNERABLE_TIME,
+            player_invulnerable_blink_time: PLAYER_INVULNERABLE_BLINK_TIME,
+            player_safe_spawn_radius: PLAYER_SAFE_SPAWN_RADIUS,
+            ball_large_diameter: BALL_LARGE_DIAMETER,
+            ball_medium_diameter: BALL_MEDIUM_DIAMETER,
+            ball_small_diameter: BALL_SMALL_DIAMETER,
+            ball_large_score: BALL_LARGE_SCORE,
+            ball_medium_score: BALL_MEDIUM_SCORE,
+            ball_small_score: BALL_SMALL_SCORE,
+            ball_speed: BALL_SPEED,
+            ball_split_angle: BALL_SPLIT_ANGLE,
+            ball_turn_rate: BALL_TURN_RATE,
+            ball_orbit_radius: BALL_ORBIT_RADIUS,
+            ball_orbit_turn_rate: BALL_ORBIT_TURN_RATE,
+            wave_spawn_cooldown: WAVE_SPAWN_COOLDOWN,
+            wave_break_time: WAVE_BREAK_TIME,
+            wave_base_count: WAVE_BASE_COUNT,
+            wave_count_increment: WAVE_COUNT_INCREMENT,
+            wave_base_speed: WAVE_BASE_SPEED,
+            wave_speed_increment: WAVE_SPEED_INCREMENT,
+            bullet_radius: BULLET_RADIUS,
+            bullet_speed: BULLET_SPEED,
+            bullet_cooldown: BULLET_COOLDOWN,
+            bullet_max_travel: BULLET_MAX_TRAVEL,
+            laser_length: LASER_LENGTH,
+            laser_width: LASER_WIDTH,
+            laser_energy_max: LASER_ENERGY_MAX,
+            laser_energy_drain: LASER_ENERGY_DRAIN,
+            laser_energy_recharge: LASER_ENERGY_RECHARGE,
+            laser_overheat_recovery: LASER_OVERHEAT_RECOVERY,
+            background_color: BACKGROUND_COLOR,
+            player_colors: PLAYER_COLORS,
+            ball_color: BALL_COLOR,
+            bullet_color: BULLET_COLOR,
+            laser_color: LASER_COLOR,
+            laser_overheated_color: LASER_OVERHEATED_COLOR,
+            text_color: TEXT_COLOR,
+            score_color: SCORE_COLOR,
+            title_color: TITLE_COLOR,
+            screen_overlay_color: SCREEN_OVERLAY_COLOR,
+        }
+    }
 }
 
-impl Default for BallCooldown {
-    fn default() -> Self {
-        BallCooldown {
-            a: Timer::from_seconds(BALL_COOLDOWN, TimerMode::Once),
+impl AsteroidsConfig {
+    pub fn text_colors(&self) -> TextColors {
+        TextColors {
+            overlay: self.screen_overlay_color,
+            title: self.title_color,
+            text: self.text_color,
+            value: self.score_color,
         }
     }
 }
+
+impl Config for AsteroidsConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        let mut error = ConfigError::default();
+        error.positive_size("player_size", self.player_size);
+        error.check(
+            self.player_spawn_points
+                .iter()
+                .all(|point| point.is_finite()),
+            "`player_spawn_points` must be finite",
+        );
+        error.positive("player_speed", self.player_speed);
+        error.positive("player_acceleration", self.player_acceleration);
+        error.positive("player_braking_multiplier", self.player_braking_multiplier);
+        error.check(
+            self.player_starting_lives > 0,
+            "`player_starting_lives` must be at least 1",
+        );
+        error.non_negative("player_invulnerable_time", self.player_invulnerable_time);
+        // The blinking toggles once per interval, divided by it
+        error.positive(
+            "player_invulnerable_blink_time",
+            self.player_invulnerable_blink_time,
+        );
+        error.non_negative("player_safe_spawn_radius", self.player_safe_spawn_radius);
+        error.positive("ball_large_diameter", self.ball_large_diameter);
+        error.positive("ball_medium_diameter", self.ball_medium_diameter);
+        error.positive("ball_small_diameter", self.ball_small_diameter);
+        error.positive("ball_speed", self.ball_speed);
+        error.non_negative("ball_split_angle", self.ball_split_angle);
+        error.non_negative("ball_turn_rate", self.ball_turn_rate);
+        error.positive("ball_orbit_radius", self.ball_orbit_radius);
+        error.non_negative("ball_orbit_turn_rate", self.ball_orbit_turn_rate);
+        // Repeating timers can't be empty
+        error.positive("wave_spawn_cooldown", self.wave_spawn_cooldown);
+        error.non_negative("wave_break_time", self.wave_break_time);
+        error.check(
+            self.wave_base_count > 0,
+            "`wave_base_count` must be at least 1",
+        );
+        error.positive("wave_base_speed", self.wave_base_speed);
+        error.non_negative("wave_speed_increment", self.wave_speed_increment);
+        error.positive("bullet_radius", self.bullet_radius);
+        error.positive("bullet_speed", self.bullet_speed);
+        error.non_negative("bullet_cooldown", self.bullet_cooldown);
+        error.positive("bullet_max_travel", self.bullet_max_travel);
+        error.positive("laser_length", self.laser_length);
+        error.positive("laser_width", self.laser_width);
+        error.positive("laser_energy_max", self.laser_energy_max);
+        error.non_negative("laser_energy_drain", self.laser_energy_drain);
+        error.non_negative("laser_energy_recharge", self.laser_energy_recharge);
+        error.positive("laser_overheat_recovery", self.laser_overheat_recovery);
+        error.check(
+            self.laser_overheat_recovery <= self.laser_energy_max,
+            format!(
+                "`laser_overheat_recovery` must be at most `laser_energy_max`, got {} over {}",
+                self.laser_overheat_recovery, self.laser_energy_max
+            ),
+        );
+        error.into_result()
+    }
+}
+
+// Side of the window the balls of a wave come in from
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SpawnEdge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+// What a wave throws at the players
+#[derive(Clone, Debug)]
+pub struct Wave {
+    pub count: u32,
+    // Sizes of the balls, cycled through as they spawn
+    pub sizes: Vec<BallSize>,
+    // Behaviours of the balls, cycled through as they spawn
+    pub behaviours: Vec<BallBehaviour>,
+    pub speed: f32,
+    pub edges: Vec<SpawnEdge>,
+}
+
+impl Wave {
+    // Waves get bigger, faster, and come from more sides as the game goes on
+    pub fn nth(number: u32, config: &AsteroidsConfig) -> Self {
+        let n = number.saturating_sub(1);
+
+        let sizes = match number {
+            0..=2 => vec![BallSize::Large],
+            3..=4 => vec![BallSize::Large, BallSize::Medium],
+            _ => vec![BallSize::Large, BallSize::Medium, BallSize::Small],
+        };
+
+        let seek = BallBehaviour::Seek {
+            turn_rate: config.ball_turn_rate,
+        };
+        let behaviours = match number {
+            0..=1 => vec![seek],
+            2..=3 => vec![seek, BallBehaviour::Drift],
+            _ => vec![
+                seek,
+                BallBehaviour::Drift,
+                BallBehaviour::Orbit {
+                    radius: config.ball_orbit_radius,
+                    turn_rate: config.ball_orbit_turn_rate,
+                },
+                BallBehaviour::Flee {
+                    turn_rate: config.ball_turn_rate,
+                },
+            ],
+        };
+
+        let edges = match number {
+            0..=1 => vec![SpawnEdge::Left, SpawnEdge::Right],
+            2 => vec![SpawnEdge::Top, SpawnEdge::Bottom],
+            _ => vec![
+                SpawnEdge::Top,
+                SpawnEdge::Bottom,
+                SpawnEdge::Left,
+                SpawnEdge::Right,
+            ],
+        };
+
+        Wave {
+            count: config.wave_base_count + n * config.wave_count_increment,
+            sizes,
+            behaviours,
+            speed: (config.wave_base_speed + n as f32 * config.wave_speed_increment)
+                .min(config.ball_speed),
+            edges,
+        }
+    }
+}
+
+// Spawns the balls of the current wave one by one, and starts the next wave
+// once the players have cleared the field
+#[derive(Resource)]
+pub struct WaveDirector {
+    pub number: u32,
+    pub wave: Wave,
+    // Balls of the current wave spawned so far
+    pub spawned: u32,
+    pub spawn_cooldown: Timer,
+    pub break_cooldown: Timer,
+}
+
+impl WaveDirector {
+    pub fn new(config: &AsteroidsConfig) -> Self {
+        WaveDirector {
+            number: 1,
+            wave: Wave::nth(1, config),
+            spawned: 0,
+            spawn_cooldown: Timer::from_seconds(config.wave_spawn_cooldown, TimerMode::Repeating),
+            break_cooldown: Timer::from_seconds(config.wave_break_time, TimerMode::Once),
+        }
+    }
+
+    pub fn start_next_wave(&mut self, config: &AsteroidsConfig) {
+        self.number += 1;
+        self.wave = Wave::nth(self.number, config);
+        self.spawned = 0;
+        self.spawn_cooldown.reset();
+        self.break_cooldown.reset();
+    }
+
+    // Picks up changes to the config without restarting the timers
+    pub fn retime(&mut self, config: &AsteroidsConfig) {
+        self.spawn_cooldown
+            .set_duration(Duration::from_secs_f32(config.wave_spawn_cooldown));
+        self.break_cooldown
+            .set_duration(Duration::from_secs_f32(config.wave_break_time));
+    }
+
+    pub fn finished_spawning(&self) -> bool {
+        self.spawned >= self.wave.count
+    }
+}
+
+impl Default for WaveDirector {
+    fn default() -> Self {
+        WaveDirector::new(&AsteroidsConfig::default())
+    }
+}
+
+// Scores of the players of the last run by slot, shown on the game over screen.
+// A player's score lands here when they're out of lives or leave.
+#[derive(Resource, Deref, DerefMut, Default)]
+pub struct FinalScore {
+    pub a: BTreeMap<usize, usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_sandbox::config::parse_config;
+
+    #[test]
+    fn shipped_config_matches_the_defaults() {
+        let text = include_str!("../../assets/config/asteroids.ron");
+
+        assert_eq!(
+            parse_config::<AsteroidsConfig>(text.as_bytes()),
+            Ok(AsteroidsConfig::default())
+        );
+    }
+
+    #[test]
+    fn missing_fields_keep_their_default() {
+        let config = parse_config::<AsteroidsConfig>(b"(player_acceleration: 100.0)").unwrap();
+
+        assert_eq!(config.player_acceleration, 100.0);
+        assert_eq!(config.player_speed, PLAYER_SPEED);
+    }
+
+    #[test]
+    fn invalid_values_are_all_reported() {
+        let error = parse_config::<AsteroidsConfig>(b"(player_speed: -1.0, wave_base_count: 0)")
+            .unwrap_err();
+
+        assert_eq!(error.problems.len(), 2);
+    }
+
+    #[test]
+    fn the_laser_recovers_within_its_capacity() {
+        let error = parse_config::<AsteroidsConfig>(
+            b"(laser_energy_max: 40.0, laser_overheat_recovery: 50.0)",
+        )
+        .unwrap_err();
+
+        assert_eq!(error.problems.len(), 1);
+    }
+
+    #[test]
+    fn unknown_fields_are_an_error() {
+        assert!(parse_config::<AsteroidsConfig>(b"(player_sped: 100.0)").is_err());
+    }
+}
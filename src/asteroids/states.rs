@@ -0,0 +1,30 @@
+use bevy::prelude::*;
+
+#[derive(States, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[states(scoped_entities)]
+pub enum GameState {
+    #[default]
+    Menu,
+    Settings,
+    Playing,
+    Paused,
+    GameOver,
+}
+
+/// Exists while a run is in progress, paused or not.
+///
+/// Everything belonging to a run (player, balls, bullets, HUD) is scoped to this state,
+/// so leaving the run despawns it and the next run starts from a clean world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InGame;
+
+impl ComputedStates for InGame {
+    type SourceStates = GameState;
+
+    fn compute(sources: GameState) -> Option<Self> {
+        match sources {
+            GameState::Playing | GameState::Paused => Some(InGame),
+            GameState::Menu | GameState::Settings | GameState::GameOver => None,
+        }
+    }
+}
@@ -1,73 +1,186 @@
+pub mod screens;
+
+pub use screens::*;
+
 use super::bundles::*;
 use super::components::*;
 use super::constants::*;
-use super::events::*;
 use super::resources::*;
-use bevy::math::bounding::{Aabb2d, BoundingCircle, IntersectsVolume};
+use super::states::*;
+use bevy::ecs::entity::EntityHashSet;
+use bevy::input::gamepad::GamepadConnectionEvent;
+use bevy::math::bounding::BoundingVolume as _;
 use bevy::prelude::*;
 use bevy::render::camera::RenderTarget;
 use bevy::window::PrimaryWindow;
 use bevy::window::WindowRef;
 use bevy_rapier2d::prelude::*;
-
-// Add the game's entities to our world
-pub fn setup(
-    mut window: Single<&mut Window>,
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    asset_server: Res<AssetServer>,
-) {
+use bevy_sandbox::collision::*;
+use bevy_sandbox::collision_sound::*;
+use bevy_sandbox::input::*;
+use bevy_sandbox::rng::GameRng;
+use bevy_sandbox::scoreboard::*;
+use bevy_sandbox::screen::{ScreenStyle, restyle_text};
+use std::time::Duration;
+
+// Add the entities that live for the whole session to our world
+pub fn setup(mut window: Single<&mut Window>, mut commands: Commands) {
     // Set up the window
     window.set_maximized(true);
 
     // Camera
     commands.spawn(Camera2d);
+}
 
-    // Player
-    commands.spawn(PlayerBundle::new(&mut meshes, &mut materials));
+// Add the entities of a new run to our world
+// Everything spawned here is scoped to `InGame`, so it is cleaned up when the run ends
+pub fn setup_game(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    config: Res<AsteroidsConfig>,
+    scoreboard_style: Res<ScoreboardStyle>,
+    mut rng: ResMut<GameRng>,
+    q_gamepad: Query<Entity, With<Gamepad>>,
+) {
+    // Timers, results and random numbers from the previous run must not leak into this one
+    rng.start_run(config.seed);
+    commands.insert_resource(WaveDirector::new(&config));
+    commands.insert_resource(FinalScore::default());
+
+    // Scoreboard, with the wave on top and a line per player below
+    let scoreboard = commands
+        .spawn((
+            scoreboard_style.root(),
+            StateScoped(InGame),
+            children![(scoreboard_style.line("Wave: ", "1"), WaveUi)],
+        ))
+        .id();
+
+    // Players, the keyboard one and one per gamepad
+    let sources =
+        std::iter::once(InputSource::Keyboard).chain(q_gamepad.iter().map(InputSource::Gamepad));
+
+    for (slot, source) in sources.enumerate() {
+        spawn_player(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            &config,
+            &scoreboard_style,
+            scoreboard,
+            PlayerSlot { a: slot },
+            source,
+        );
+    }
+}
 
-    // Sound
-    commands.insert_resource(CollisionSound {
-        a: asset_server.load("sounds/breakout_collision.ogg"),
-    });
+// Spawns the ship of a player and its line in the scoreboard
+fn spawn_player(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    config: &AsteroidsConfig,
+    scoreboard_style: &ScoreboardStyle,
+    scoreboard: Entity,
+    slot: PlayerSlot,
+    source: InputSource,
+) {
+    let player_entity = commands
+        .spawn(PlayerBundle::new(meshes, materials, config, slot, source))
+        .id();
+
+    let span = |text: String, color: Color| scoreboard_style.value(text, color);
 
-    // Scoreboard
     commands.spawn((
-        Text::new("Score: "),
-        TextFont {
-            font_size: SCOREBOARD_FONT_SIZE,
-            ..default()
-        },
-        TextColor(TEXT_COLOR),
-        ScoreboardUi,
-        Node {
-            position_type: PositionType::Absolute,
-            top: SCOREBOARD_TEXT_PADDING,
-            left: SCOREBOARD_TEXT_PADDING,
-            ..default()
-        },
-        children![(
-            TextSpan::new("0"),
-            TextFont {
-                font_size: SCOREBOARD_FONT_SIZE,
-                ..default()
-            },
-            TextColor(SCORE_COLOR),
-        )],
+        scoreboard_style.label(format!("{}   Score: ", slot.name()), slot.color(config)),
+        HudOf(player_entity),
+        ChildOf(scoreboard),
+        children![
+            span("0".to_string(), config.score_color),
+            span("   Lives: ".to_string(), config.text_color),
+            span(config.player_starting_lives.to_string(), config.score_color),
+            span("   Laser: ".to_string(), config.text_color),
+            span("100%".to_string(), config.score_color),
+            span("   Aim: ".to_string(), config.text_color),
+            span(
+                AimMode::available(source)[0].name().to_string(),
+                config.score_color
+            ),
+        ],
     ));
+}
 
-    /* Create the ground. */
-    commands
-        .spawn(Collider::cuboid(500.0, 50.0))
-        .insert(Transform::from_xyz(0.0, -100.0, 0.0));
+// Players leaving take their score with them to the game over screen
+fn retire_player(
+    commands: &mut Commands,
+    final_score: &mut FinalScore,
+    player_entity: Entity,
+    slot: &PlayerSlot,
+    score: &Score,
+) {
+    final_score.insert(slot.a, score.a);
+    commands.entity(player_entity).despawn();
+}
 
-    /* Create the bouncing ball. */
-    commands
-        .spawn(RigidBody::Dynamic)
-        .insert(Collider::ball(50.0))
-        .insert(Restitution::coefficient(0.7))
-        .insert(Transform::from_xyz(0.0, 400.0, 0.0));
+// Gamepads connected during a run join it with a new ship, disconnected ones leave it
+pub fn handle_gamepad_connections(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    config: Res<AsteroidsConfig>,
+    scoreboard_style: Res<ScoreboardStyle>,
+    mut connection_events: EventReader<GamepadConnectionEvent>,
+    scoreboard: Single<Entity, With<ScoreboardUi>>,
+    q_player: Query<(Entity, &ControlledBy, &PlayerSlot, &Score), With<Player>>,
+    mut final_score: ResMut<FinalScore>,
+) {
+    for connection_event in connection_events.read() {
+        let source = InputSource::Gamepad(connection_event.gamepad);
+        let player = q_player
+            .iter()
+            .find(|(_, controlled_by, _, _)| controlled_by.a == source);
+
+        match (connection_event.connected(), player) {
+            (true, None) => {
+                // Take the first slot nobody had this run, as the final scores of players
+                // that left or are out are kept by their slot
+                let slot = (0..)
+                    .find(|slot| {
+                        !final_score.contains_key(slot)
+                            && q_player
+                                .iter()
+                                .all(|(_, _, player_slot, _)| player_slot.a != *slot)
+                    })
+                    .unwrap_or_default();
+
+                spawn_player(
+                    &mut commands,
+                    &mut meshes,
+                    &mut materials,
+                    &config,
+                    &scoreboard_style,
+                    *scoreboard,
+                    PlayerSlot { a: slot },
+                    source,
+                );
+            }
+            (false, Some((player_entity, _, slot, score))) => {
+                retire_player(&mut commands, &mut final_score, player_entity, slot, score);
+            }
+            _ => {}
+        }
+    }
+}
+
+// The run is over once every player is out
+pub fn check_game_over(
+    q_player: Query<(), With<Player>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if q_player.is_empty() {
+        next_state.set(GameState::GameOver);
+    }
 }
 
 trait IfZeroSet {
@@ -84,104 +197,78 @@ impl IfZeroSet for f32 {
 
 pub fn calculate_acceleration(
     mut commands: Commands,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    q_gamepad: Query<&Gamepad>,
-    mut query: Query<(Entity, &Velocity, &mut Acceleration, Option<&Player>)>,
-) {
-    for (entity, velocity, mut acceleration, maybe_player) in &mut query {
-        if maybe_player.is_some() {
-            acceleration.x = 0.0;
-            acceleration.y = 0.0;
-
-            let mut is_braking = false;
-
-            let key_left = keyboard_input.pressed(KeyCode::ArrowLeft);
-            let key_right = keyboard_input.pressed(KeyCode::ArrowRight);
-            let key_up = keyboard_input.pressed(KeyCode::ArrowUp);
-            let key_down = keyboard_input.pressed(KeyCode::ArrowDown);
-
-            if key_left && !key_right {
-                acceleration.x.if_zero_set(-PLAYER_ACCELERATION);
-            } else if key_right && !key_left {
-                acceleration.x.if_zero_set(PLAYER_ACCELERATION);
-            }
+    action_input: ActionInput,
+    config: Res<AsteroidsConfig>,
+    mut query: Query<
+        (
+            Entity,
+            &Velocity,
+            &mut Acceleration,
+            &ControlledBy,
+            &AimMode,
+        ),
+        (With<Player>, Without<Respawning>),
+    >,
+) {
+    for (entity, velocity, mut acceleration, controlled_by, aim_mode) in &mut query {
+        acceleration.a = action_input.axis_by(controlled_by.a, aim_mode.thrust_action())
+            * config.player_acceleration;
 
-            if key_down && !key_up {
-                acceleration.y.if_zero_set(-PLAYER_ACCELERATION);
-            } else if key_up && !key_down {
-                acceleration.y.if_zero_set(PLAYER_ACCELERATION);
-            }
+        let mut is_braking = false;
 
-            for gamepad in q_gamepad {
-                if let Some(left_stick_x) = gamepad.get(GamepadAxis::LeftStickX) {
-                    if left_stick_x.abs() > 0.01 {
-                        // If the left stick is moved, set the acceleration
-                        acceleration
-                            .x
-                            .if_zero_set(left_stick_x * PLAYER_ACCELERATION);
-                    }
-                }
+        // BRAKING
+        if acceleration.x * velocity.linvel.x < 0.0 {
+            // If the acceleration is in the opposite direction of the velocity, apply braking
+            let acc_x = acceleration.x * config.player_braking_multiplier;
+            acceleration.x.if_zero_set(acc_x);
 
-                if let Some(left_stick_y) = gamepad.get(GamepadAxis::LeftStickY) {
-                    if left_stick_y.abs() > 0.01 {
-                        // If the left stick is moved, set the acceleration
-                        acceleration
-                            .y
-                            .if_zero_set(left_stick_y * PLAYER_ACCELERATION);
-                    }
-                }
-            }
+            is_braking = true;
+        }
 
-            // BRAKING
-            if acceleration.x * velocity.linvel.x < 0.0 {
-                // If the acceleration is in the opposite direction of the velocity, apply braking
-                let acc_x = acceleration.x * PLAYER_BRAKING_MULTIPLIER;
-                acceleration.x.if_zero_set(acc_x);
+        if acceleration.y * velocity.linvel.y < 0.0 {
+            // If the acceleration is in the opposite direction of the velocity, apply braking
+            let acc_y = acceleration.y * config.player_braking_multiplier;
+            acceleration.y.if_zero_set(acc_y);
 
-                is_braking = true;
-            }
+            is_braking = true;
+        }
 
-            if acceleration.y * velocity.linvel.y < 0.0 {
-                // If the acceleration is in the opposite direction of the velocity, apply braking
-                let acc_y = acceleration.y * PLAYER_BRAKING_MULTIPLIER;
-                acceleration.y.if_zero_set(acc_y);
+        // If no input, apply braking
+        if acceleration.x == 0.0 && acceleration.y == 0.0 {
+            if velocity.linvel.x != 0.0 {
+                acceleration.x = -velocity.linvel.x.signum()
+                    * config.player_acceleration
+                    * config.player_braking_multiplier;
 
                 is_braking = true;
             }
+            if velocity.linvel.y != 0.0 {
+                acceleration.y = -velocity.linvel.y.signum()
+                    * config.player_acceleration
+                    * config.player_braking_multiplier;
 
-            // If no input, apply braking
-            if acceleration.x == 0.0 && acceleration.y == 0.0 {
-                if velocity.linvel.x != 0.0 {
-                    acceleration.x = -velocity.linvel.x.signum()
-                        * PLAYER_ACCELERATION
-                        * PLAYER_BRAKING_MULTIPLIER;
-
-                    is_braking = true;
-                }
-                if velocity.linvel.y != 0.0 {
-                    acceleration.y = -velocity.linvel.y.signum()
-                        * PLAYER_ACCELERATION
-                        * PLAYER_BRAKING_MULTIPLIER;
-
-                    is_braking = true;
-                }
-            }
-
-            if is_braking {
-                commands.entity(entity).insert(IsBraking);
-            } else {
-                commands.entity(entity).remove::<IsBraking>();
+                is_braking = true;
             }
+        }
 
-            // Normalize the acceleration vector to ensure consistent acceleration magnitude
-            acceleration.a = acceleration.normalize_or_zero() * PLAYER_ACCELERATION;
+        if is_braking {
+            commands.entity(entity).insert(IsBraking);
+        } else {
+            commands.entity(entity).remove::<IsBraking>();
         }
+
+        // Normalize the acceleration vector to ensure consistent acceleration magnitude
+        acceleration.a = acceleration.normalize_or_zero() * config.player_acceleration;
     }
 }
 
 pub fn calculate_player_velocity(
     time: Res<Time>,
-    mut query: Query<(&mut Velocity, &Acceleration, Option<&IsBraking>), With<Player>>,
+    config: Res<AsteroidsConfig>,
+    mut query: Query<
+        (&mut Velocity, &Acceleration, Option<&IsBraking>),
+        (With<Player>, Without<Respawning>),
+    >,
 ) {
     for (mut velocity, acceleration, maybe_is_braking) in &mut query {
         let vel_x_sign = velocity.linvel.x.signum();
@@ -201,170 +288,280 @@ pub fn calculate_player_velocity(
             }
         }
 
-        // Limit the player's speed to PLAYER_SPEED
+        // Limit the player's speed to the configured top speed
         let speed = velocity.linvel.length();
-        if speed > PLAYER_SPEED {
-            velocity.linvel = velocity.linvel.normalize_or_zero() * PLAYER_SPEED;
+        if speed > config.player_speed {
+            velocity.linvel = velocity.linvel.normalize_or_zero() * config.player_speed;
         }
     }
 }
 
+// Every ball cruises at its top speed, turning as its behaviour tells it
+// with respect to the player nearest to it
 pub fn calculate_ball_velocity(
-    mut q_balls: Query<(&mut Velocity, &Transform), (With<Ball>, Without<Player>)>,
-    q_players: Query<&Transform, With<Player>>,
+    time: Res<Time>,
+    mut q_balls: Query<
+        (
+            &mut Velocity,
+            &Transform,
+            &Speed,
+            &BallBehaviour,
+            Has<EnteringWindow>,
+        ),
+        (With<Ball>, Without<Player>),
+    >,
+    q_players: Query<&Transform, (With<Player>, Without<Respawning>)>,
 ) {
-    for player_transform in &q_players {
-        for (mut ball_velocity, ball_transform) in &mut q_balls {
-            let direction = player_transform.translation - ball_transform.translation;
-            ball_velocity.linvel = direction.truncate();
+    for (mut ball_velocity, ball_transform, ball_speed, ball_behaviour, is_entering) in &mut q_balls
+    {
+        let ball_position = ball_transform.translation.truncate();
+
+        let nearest_player = q_players
+            .iter()
+            .map(|player_transform| player_transform.translation.truncate())
+            .min_by(|a, b| {
+                a.distance_squared(ball_position)
+                    .total_cmp(&b.distance_squared(ball_position))
+            });
+
+        let mut heading = ball_velocity.linvel.try_normalize().unwrap_or(Vec2::X);
+
+        // Balls fly straight in before they start steering, or a fleeing ball would never show up
+        if !is_entering
+            && let Some((target, turn_rate)) =
+                ball_behaviour.steering(ball_position, nearest_player)
+        {
+            let max_angle = turn_rate * time.delta_secs();
+            let angle = heading.angle_to(target).clamp(-max_angle, max_angle);
+            heading = Vec2::from_angle(angle).rotate(heading);
         }
+
+        ball_velocity.linvel = heading * ball_speed.a;
     }
 }
 
-// * Important: must be executed after methods that affect the velocity of objects
-pub fn apply_velocity(time: Res<Time>, mut query: Query<(&mut Transform, &Velocity)>) {
-    for (mut transform, velocity) in &mut query {
-        transform.translation.x += velocity.linvel.x * time.delta_secs();
-        transform.translation.y += velocity.linvel.y * time.delta_secs();
+pub fn calculate_player_orientation(
+    action_input: ActionInput,
+    mut q_player: Query<
+        (&mut Transform, &ControlledBy, &AimMode),
+        (With<Player>, Without<Respawning>),
+    >,
+) {
+    let cursor = action_input.pointer();
+
+    for (mut transform, controlled_by, aim_mode) in &mut q_player {
+        let aim = match aim_mode.aim_action() {
+            Some(aim_action) => action_input.axis_by(controlled_by.a, aim_action),
+            None => cursor.map_or(Vec2::ZERO, |cursor| {
+                cursor - transform.translation.truncate()
+            }),
+        };
+
+        if aim != Vec2::ZERO {
+            // Calculate the angle based on the aiming direction
+            let angle = aim.y.atan2(aim.x);
+            transform.rotation = Quat::from_rotation_z(angle);
+        }
     }
 }
 
-pub fn calculate_player_orientation(
-    q_gamepad: Query<&Gamepad>,
-    mut q_player: Query<&mut Transform, With<Player>>,
-) {
-    for mut transform in &mut q_player {
-        for gamepad in &q_gamepad {
-            let right_stick = gamepad.right_stick();
-            if right_stick.length() > 0.01 {
-                // Calculate the angle based on the left stick vector
-                let angle = right_stick.y.atan2(right_stick.x);
-                transform.rotation = Quat::from_rotation_z(angle);
-            }
+// Switches a player to the next aim mode their device supports
+pub fn cycle_aim_mode(
+    action_input: ActionInput,
+    mut q_player: Query<(&ControlledBy, &mut AimMode), With<Player>>,
+) {
+    for (controlled_by, mut aim_mode) in &mut q_player {
+        if action_input.just_pressed_by(controlled_by.a, ButtonAction::CycleAimMode) {
+            *aim_mode = aim_mode.next(controlled_by.a);
         }
     }
 }
 
 pub fn spawn_attacks(
-    time: Res<Time>,
     mut commands: Commands,
-    r_keyboard_input: Res<ButtonInput<KeyCode>>,
-    q_gamepad: Query<&Gamepad>,
+    action_input: ActionInput,
+    config: Res<AsteroidsConfig>,
     mut r_mesh: ResMut<Assets<Mesh>>,
     mut r_material: ResMut<Assets<ColorMaterial>>,
     mut q_player: Query<
-        (Entity, &Transform, &mut BulletCooldown, &mut LaserCooldown),
+        (
+            Entity,
+            &Transform,
+            &mut BulletCooldown,
+            &LaserEnergy,
+            &ControlledBy,
+            Option<&Anchored>,
+            Has<Respawning>,
+        ),
         With<Player>,
     >,
 ) {
-    for (player_entity, player_transform, mut bullet_cooldown, mut laser_cooldown) in &mut q_player
+    for (
+        player_entity,
+        player_transform,
+        mut bullet_cooldown,
+        laser_energy,
+        controlled_by,
+        maybe_anchored,
+        is_respawning,
+    ) in &mut q_player
     {
-        bullet_cooldown.tick(time.delta());
+        bullet_cooldown.set_duration(Duration::from_secs_f32(config.bullet_cooldown));
+
+        if !is_respawning
+            && action_input.pressed_by(controlled_by.a, ButtonAction::FireBullet)
+            && bullet_cooldown.finished()
+        {
+            // Spawn a bullet at the paddle's position
+            let bullet_position = player_transform;
+            let bullet_velocity = player_transform.rotation.mul_vec3(Vec3::X) * config.bullet_speed;
+
+            commands.spawn(BulletBundle::new(
+                &mut r_mesh,
+                &mut r_material,
+                &config,
+                *bullet_position,
+                bullet_velocity,
+                config.bullet_max_travel,
+                player_entity,
+            ));
 
-        let gamepad_fire_bullet = q_gamepad
-            .iter()
-            .any(|gamepad| gamepad.pressed(GAMEPAD_BULLET_BUTTON));
+            // Reset the bullet cooldown timer
+            bullet_cooldown.reset();
+        }
 
-        if gamepad_fire_bullet || r_keyboard_input.pressed(KEYBOARD_BULLET_BUTTON) {
-            if bullet_cooldown.finished() {
-                // Spawn a bullet at the paddle's position
-                let bullet_position = player_transform;
-                let bullet_velocity = player_transform.rotation.mul_vec3(Vec3::X) * BULLET_SPEED;
+        let fire_laser = !is_respawning
+            && action_input.pressed_by(controlled_by.a, ButtonAction::FireLaser)
+            && laser_energy.can_fire();
 
-                commands.spawn(BulletBundle::new(
+        // The beam lives while the button is held, there's never more than one per player
+        match (fire_laser, maybe_anchored) {
+            (true, None) => {
+                commands.spawn(LaserBundle::new(
                     &mut r_mesh,
                     &mut r_material,
-                    *bullet_position,
-                    bullet_velocity,
+                    &config,
+                    *player_transform,
+                    player_entity,
                 ));
-
-                // Reset the bullet cooldown timer
-                bullet_cooldown.reset();
             }
+            (false, Some(anchored)) => {
+                for laser_entity in anchored.iter() {
+                    commands.entity(laser_entity).despawn();
+                }
+            }
+            _ => {}
         }
+    }
+}
 
-        laser_cooldown.tick(time.delta());
-
-        let gamepad_fire_laser = q_gamepad
-            .iter()
-            .any(|gamepad| gamepad.pressed(GAMEPAD_LASER_BUTTON));
-
-        if gamepad_fire_laser || r_keyboard_input.pressed(KEYBOARD_LASER_BUTTON) {
-            if laser_cooldown.finished() {
-                let laser = commands
-                    .spawn(LaserBundle::new(
-                        &mut r_mesh,
-                        &mut r_material,
-                        *player_transform,
-                    ))
-                    .id();
-
-                commands.entity(player_entity).add_child(laser);
-
-                // Reset the laser cooldown timer
-                laser_cooldown.reset();
-            }
+// Firing the laser drains its energy, which recharges while it's off
+pub fn update_laser_energy(
+    time: Res<Time>,
+    config: Res<AsteroidsConfig>,
+    mut q_player: Query<(&mut LaserEnergy, Has<Anchored>), With<Player>>,
+) {
+    for (mut laser_energy, is_firing) in &mut q_player {
+        if is_firing {
+            laser_energy.drain(config.laser_energy_drain * time.delta_secs());
+        } else {
+            laser_energy.recharge(config.laser_energy_recharge * time.delta_secs(), &config);
         }
     }
 }
 
-// TODO: arreglar las colisiones, estaban basadas en los scale, pero ahora los scale son (1, 1) y bevy cree que tienen ese tamaño. Cambiarlo a que use el tamaño del mesh
+// Keeps every laser beam on the nose of the player that fires it
+pub fn anchor_lasers(
+    q_player: Query<&Transform, (With<Player>, Without<Laser>)>,
+    mut q_laser: Query<(&AnchorTo, &mut Transform), With<Laser>>,
+) {
+    for (anchor_to, mut laser_transform) in &mut q_laser {
+        if let Ok(player_transform) = q_player.get(anchor_to.target) {
+            anchor_to.anchor(&mut laser_transform, player_transform);
+            // The beam may have been cut short by a ball on the previous tick
+            laser_transform.scale = Vec3::ONE;
+        }
+    }
+}
 
+// Spawns the balls of the current wave just outside the window, heading in,
+// and starts the next wave once every ball has been destroyed
 pub fn spawn_balls(
     time: Res<Time>,
-    mut ball_cooldown: ResMut<BallCooldown>,
+    config: Res<AsteroidsConfig>,
+    mut rng: ResMut<GameRng>,
+    mut wave_director: ResMut<WaveDirector>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    q_window: Query<&Window>,
-) {
-    ball_cooldown.tick(time.delta());
-
-    if ball_cooldown.finished() {
-        for window in &q_window {
-            // Spawn a ball outside the window at a random position
-            let ball_x = match getrandom::u32() {
-                Ok(x) => (x as f32 / u32::MAX as f32 - 0.5) * window.width(),
-                Err(_) => {
-                    info!("Failed to get random x coordinate for ball");
-                    return;
-                }
-            };
-            let ball_y = match getrandom::u32() {
-                Ok(y) => (y as f32 / u32::MAX as f32 - 0.5) * window.height(),
-                Err(_) => {
-                    info!("Failed to get random y coordinate for ball");
-                    return;
-                }
-            };
-
-            let ball_position = Vec2::new(ball_x, ball_y);
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_ball: Query<(), With<Ball>>,
+) {
+    wave_director.retime(&config);
 
-            // Check if the ball is inside the window bounds
-            let inside_window = false; /* ball_position.x >= window.width()
-            || ball_position.x <= -window.width()
-            || ball_position.y >= window.height()
-            || ball_position.y <= -window.height();*/
+    if wave_director.finished_spawning() {
+        // Wait for the field to be cleared before the next wave
+        if q_ball.is_empty() {
+            wave_director.break_cooldown.tick(time.delta());
 
-            if !inside_window {
-                // Spawn the ball only if it's outside the window
-                commands.spawn(BallBundle::new(&mut meshes, &mut materials, ball_position));
+            if wave_director.break_cooldown.finished() {
+                wave_director.start_next_wave(&config);
             }
-
-            // Reset the bullet cooldown timer
-            ball_cooldown.reset();
         }
+        return;
+    }
+
+    wave_director.spawn_cooldown.tick(time.delta());
+
+    if !wave_director.spawn_cooldown.just_finished() {
+        return;
     }
+
+    let Ok(window) = q_window.single() else {
+        return;
+    };
+
+    let wave = &wave_director.wave;
+    let edge = wave.edges[rng.index(wave.edges.len())];
+    let ball_size = wave.sizes[wave_director.spawned as usize % wave.sizes.len()];
+    let ball_behaviour = wave.behaviours[wave_director.spawned as usize % wave.behaviours.len()];
+    let speed = wave.speed;
+
+    // Place the ball so it touches the window border from the outside
+    let half_width = window.width() / 2.0 + ball_size.radius(&config);
+    let half_height = window.height() / 2.0 + ball_size.radius(&config);
+    let along = rng.unit() - 0.5;
+
+    let (ball_position, heading) = match edge {
+        SpawnEdge::Top => (Vec2::new(along * window.width(), half_height), Vec2::NEG_Y),
+        SpawnEdge::Bottom => (Vec2::new(along * window.width(), -half_height), Vec2::Y),
+        SpawnEdge::Left => (Vec2::new(-half_width, along * window.height()), Vec2::X),
+        SpawnEdge::Right => (Vec2::new(half_width, along * window.height()), Vec2::NEG_X),
+    };
+
+    commands.spawn((
+        BallBundle::new(
+            &mut meshes,
+            &mut materials,
+            &config,
+            ball_position,
+            ball_size,
+            heading * speed,
+            speed,
+            ball_behaviour,
+        ),
+        // The ball starts outside the window, it must not wrap before it's come in
+        EnteringWindow,
+    ));
+
+    wave_director.spawned += 1;
 }
 
 pub fn despawn(
-    time: Res<Time>,
     mut commands: Commands,
-    mut q_despawn: Query<(Entity, &mut DespawnCooldown, Option<&ChildOf>)>,
+    q_despawn: Query<(Entity, &DespawnCooldown, Option<&ChildOf>)>,
 ) {
-    for (despawn_entity, mut despawn_cooldown, maybe_child_of) in &mut q_despawn {
-        despawn_cooldown.tick(time.delta());
-
+    for (despawn_entity, despawn_cooldown, maybe_child_of) in &q_despawn {
         if despawn_cooldown.finished() {
             if let Some(child_of) = maybe_child_of {
                 // If the entity has a parent, we need to remove it from the parent's children
@@ -378,214 +575,727 @@ pub fn despawn(
 }
 
 pub fn update_scoreboard(
-    score_query: Single<(Entity, &Score), (With<ScoreboardUi>, With<Text>)>,
+    q_player: Query<&Score, With<Player>>,
+    q_hud: Query<(Entity, &HudOf)>,
+    mut writer: TextUiWriter,
+) {
+    for (hud_entity, hud_of) in &q_hud {
+        if let Ok(score) = q_player.get(hud_of.0) {
+            *writer.text(hud_entity, 1) = score.a.to_string();
+        }
+    }
+}
+
+pub fn update_wave_ui(
+    wave_director: Res<WaveDirector>,
+    wave_root: Single<Entity, (With<WaveUi>, With<Text>)>,
+    mut writer: TextUiWriter,
+) {
+    *writer.text(*wave_root, 1) = wave_director.number.to_string();
+}
+
+pub fn update_lives_ui(
+    q_player: Query<&Lives, With<Player>>,
+    q_hud: Query<(Entity, &HudOf)>,
+    mut writer: TextUiWriter,
+) {
+    for (hud_entity, hud_of) in &q_hud {
+        if let Ok(lives) = q_player.get(hud_of.0) {
+            *writer.text(hud_entity, 3) = lives.a.to_string();
+        }
+    }
+}
+
+pub fn update_aim_ui(
+    q_player: Query<&AimMode, With<Player>>,
+    q_hud: Query<(Entity, &HudOf)>,
     mut writer: TextUiWriter,
 ) {
-    let (score_board_ui_entity, score) = *score_query;
-    *writer.text(score_board_ui_entity, 1) = score.a.to_string();
+    for (hud_entity, hud_of) in &q_hud {
+        if let Ok(aim_mode) = q_player.get(hud_of.0) {
+            *writer.text(hud_entity, 7) = aim_mode.name().to_string();
+        }
+    }
+}
+
+// Shows the laser energy, greyed out while the laser is overheated
+pub fn update_laser_ui(
+    config: Res<AsteroidsConfig>,
+    q_player: Query<&LaserEnergy, With<Player>>,
+    q_hud: Query<(Entity, &HudOf)>,
+    mut writer: TextUiWriter,
+) {
+    for (hud_entity, hud_of) in &q_hud {
+        let Ok(laser_energy) = q_player.get(hud_of.0) else {
+            continue;
+        };
+
+        let percent = laser_energy.a / config.laser_energy_max * 100.0;
+        *writer.text(hud_entity, 5) = format!("{percent:.0}%");
+        *writer.color(hud_entity, 5) = TextColor(if laser_energy.overheated {
+            config.laser_overheated_color
+        } else {
+            config.score_color
+        });
+    }
+}
+
+// Meshes and materials change in place, as the wrap ghosts share them
+pub fn restyle_screens(
+    mut commands: Commands,
+    config: Res<AsteroidsConfig>,
+    mut screen_style: ResMut<ScreenStyle>,
+    mut scoreboard_style: ResMut<ScoreboardStyle>,
+    mut q_text: Query<&mut TextColor>,
+    mut q_background: Query<&mut BackgroundColor>,
+) {
+    commands.insert_resource(ClearColor(config.background_color));
+    restyle_text(
+        config.text_colors(),
+        &mut screen_style,
+        &mut scoreboard_style,
+        &mut q_text,
+        &mut q_background,
+    );
+}
+
+// The line of each player in the scoreboard starts in the colour of their slot
+pub fn restyle_hud(
+    config: Res<AsteroidsConfig>,
+    q_player: Query<&PlayerSlot>,
+    mut q_hud: Query<(&HudOf, &mut TextColor)>,
+) {
+    for (hud_of, mut text_color) in &mut q_hud {
+        if let Ok(slot) = q_player.get(hud_of.0) {
+            text_color.0 = slot.color(&config);
+        }
+    }
+}
+
+pub fn restyle_pieces(
+    mut commands: Commands,
+    config: Res<AsteroidsConfig>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut q_piece: Query<
+        (
+            Entity,
+            &Mesh2d,
+            &MeshMaterial2d<ColorMaterial>,
+            &mut Shape,
+            Option<&mut Collider>,
+            Option<&AnchorTo>,
+            Option<&PlayerSlot>,
+            Option<&BallSize>,
+            Has<Bullet>,
+        ),
+        Or<(With<Player>, With<Ball>, With<Bullet>, With<Laser>)>,
+    >,
+) {
+    for (entity, mesh, material, mut shape, collider, anchor_to, slot, ball_size, is_bullet) in
+        &mut q_piece
+    {
+        let (new_shape, new_mesh, color) = if let Some(slot) = slot {
+            let rectangle = PlayerBundle::rectangle(&config);
+            (
+                Shape::Rectangle(rectangle),
+                rectangle.into(),
+                slot.color(&config),
+            )
+        } else if let Some(ball_size) = ball_size {
+            let circle = Circle {
+                radius: ball_size.radius(&config),
+            };
+            (Shape::Circle(circle), circle.into(), config.ball_color)
+        } else if is_bullet {
+            let annulus = BulletBundle::annulus(&config);
+            (Shape::Annulus(annulus), annulus.into(), config.bullet_color)
+        } else {
+            let rectangle = LaserBundle::rectangle(&config);
+            (
+                Shape::Rectangle(rectangle),
+                rectangle.into(),
+                config.laser_color,
+            )
+        };
+
+        if let Some(mut collider) = collider {
+            *collider = new_shape.collider();
+        }
+        // Anchors can't be changed, only replaced
+        if let Some(anchor_to) = anchor_to {
+            commands.entity(entity).insert(AnchorTo {
+                target: anchor_to.target,
+                offset: LaserBundle::offset(&config),
+            });
+        }
+        *shape = new_shape;
+        if let Some(mesh) = meshes.get_mut(mesh) {
+            *mesh = new_mesh;
+        }
+        if let Some(material) = materials.get_mut(material) {
+            material.color = color;
+        }
+    }
+}
+
+// Returns the part of the world the camera shows in its window
+fn camera_view(
+    camera: &Camera,
+    camera_transform: &Transform,
+    q_window_primary: &Query<&Window, With<PrimaryWindow>>,
+    q_window: &Query<&Window, Without<PrimaryWindow>>,
+) -> Option<Rect> {
+    let window: &Window = match camera.target {
+        // the camera is rendering to the primary window
+        RenderTarget::Window(WindowRef::Primary) => q_window_primary.single().ok()?,
+        // the camera is rendering to some other window
+        RenderTarget::Window(WindowRef::Entity(e_window)) => q_window.get(e_window).ok()?,
+        // the camera is rendering to something else (like a texture), not a window
+        _ => return None,
+    };
+
+    Some(Rect::from_center_size(
+        camera_transform.translation.truncate(),
+        window.size(),
+    ))
 }
 
 pub fn window_collision(
+    mut commands: Commands,
     q_window_primary: Query<&Window, With<PrimaryWindow>>,
     q_window: Query<&Window, Without<PrimaryWindow>>,
     camera_query: Query<(&Camera, &Transform), With<Camera>>,
-    mut collider_query: Query<(&mut Transform, &mut Velocity), (With<Collider>, Without<Camera>)>,
+    mut collider_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Velocity,
+            &Shape,
+            &EdgeBehaviour,
+            Has<EnteringWindow>,
+        ),
+        Without<Camera>,
+    >,
 ) {
     for (camera, camera_transform) in &camera_query {
-        let window: &Window = match camera.target {
-            // the camera is rendering to the primary window
-            RenderTarget::Window(WindowRef::Primary) => q_window_primary.single().unwrap(),
-            // the camera is rendering to some other window
-            RenderTarget::Window(WindowRef::Entity(e_window)) => q_window.get(e_window).unwrap(),
-            // the camera is rendering to something else (like a texture), not a window
-            _ => {
-                // skip this camera
-                continue;
-            }
+        let Some(view) = camera_view(camera, camera_transform, &q_window_primary, &q_window) else {
+            // skip this camera
+            continue;
         };
 
-        for (mut collider_transform, mut collider_velocity) in &mut collider_query {
-            let collider_pos = collider_transform.translation;
-            let collider_size = collider_transform.scale.truncate() / 2.0;
+        for (
+            collider_entity,
+            mut collider_transform,
+            mut collider_velocity,
+            collider_shape,
+            edge_behaviour,
+            is_entering,
+        ) in &mut collider_query
+        {
+            let collider_pos = collider_transform.translation.truncate();
+            let collider_size = BoundingVolume::from_shape(collider_shape, &collider_transform)
+                .aabb()
+                .half_size();
+
+            if is_entering {
+                if view.contains(collider_pos) {
+                    commands.entity(collider_entity).remove::<EnteringWindow>();
+                }
+                continue;
+            }
 
-            let camera_pos = camera_transform.translation;
+            // Where the centre of the collider can be while it's fully inside the window
+            let inner = Rect::from_corners(view.min + collider_size, view.max - collider_size);
 
-            let half_width = window.width() / 2.0;
-            let half_height = window.height() / 2.0;
+            match edge_behaviour {
+                EdgeBehaviour::Clamp => {
+                    if !inner.contains(collider_pos) {
+                        let clamped = collider_pos.clamp(inner.min, inner.max);
+                        collider_transform.translation.x = clamped.x;
+                        collider_transform.translation.y = clamped.y;
 
-            let camera_left = camera_pos.x - half_width + collider_size.x;
-            let camera_right = camera_pos.x + half_width - collider_size.x;
-            let camera_top = camera_pos.y + half_height - collider_size.y;
-            let camera_bottom = camera_pos.y - half_height + collider_size.y;
+                        // If the collider is outside the camera's viewport, reset its velocity
+                        collider_velocity.linvel = Vec2::ZERO;
+                    }
+                }
+                EdgeBehaviour::Bounce => {
+                    let linvel = &mut collider_velocity.linvel;
+                    let translation = &mut collider_transform.translation;
+
+                    if collider_pos.x < inner.min.x {
+                        translation.x = inner.min.x;
+                        linvel.x = linvel.x.abs();
+                    } else if collider_pos.x > inner.max.x {
+                        translation.x = inner.max.x;
+                        linvel.x = -linvel.x.abs();
+                    }
 
-            let in_camera = collider_pos.x >= camera_left
-                && collider_pos.x <= camera_right
-                && collider_pos.y >= camera_bottom
-                && collider_pos.y <= camera_top;
+                    if collider_pos.y < inner.min.y {
+                        translation.y = inner.min.y;
+                        linvel.y = linvel.y.abs();
+                    } else if collider_pos.y > inner.max.y {
+                        translation.y = inner.max.y;
+                        linvel.y = -linvel.y.abs();
+                    }
+                }
+                EdgeBehaviour::Wrap => {
+                    // The centre crossing an edge moves the collider to the opposite one,
+                    // its ghosts draw the part that is still on the other side
+                    let size = view.size();
+                    let translation = &mut collider_transform.translation;
+
+                    if collider_pos.x < view.min.x {
+                        translation.x += size.x;
+                    } else if collider_pos.x > view.max.x {
+                        translation.x -= size.x;
+                    }
 
-            if !in_camera {
-                collider_transform.translation = collider_transform.translation.clamp(
-                    Vec3::new(camera_left, camera_bottom, 0.0),
-                    Vec3::new(camera_right, camera_top, 0.0),
-                );
+                    if collider_pos.y < view.min.y {
+                        translation.y += size.y;
+                    } else if collider_pos.y > view.max.y {
+                        translation.y -= size.y;
+                    }
+                }
+                EdgeBehaviour::Despawn => {
+                    let outer =
+                        Rect::from_corners(view.min - collider_size, view.max + collider_size);
 
-                // If the collider is outside the camera's viewport, reset its velocity
-                collider_velocity.linvel.x = 0.0;
-                collider_velocity.linvel.y = 0.0;
+                    if !outer.contains(collider_pos) {
+                        commands.entity(collider_entity).try_despawn();
+                    }
+                }
             }
         }
     }
 }
 
-pub fn check_for_ball_collisions(
+// Gives every wrapping entity the ghosts that draw it across the edges
+pub fn spawn_wrap_ghosts(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    q_ball: Query<(Entity, &Transform), (With<Ball>, Without<Player>)>,
-    q_player: Query<(Entity, &Transform), (With<Player>, Without<Ball>)>,
-    mut ball_collision_events: EventWriter<BallCollisionEvent>,
+    q_added: Query<
+        (
+            Entity,
+            &EdgeBehaviour,
+            &Mesh2d,
+            &MeshMaterial2d<ColorMaterial>,
+        ),
+        Added<EdgeBehaviour>,
+    >,
 ) {
-    let mut we_deh = false;
+    for (entity, edge_behaviour, mesh, material) in &q_added {
+        if *edge_behaviour != EdgeBehaviour::Wrap {
+            continue;
+        }
 
-    for (_, ball_transform) in q_ball {
-        for (_, player_transform) in q_player {
-            let collision = collision(
-                BoundingCircle::new(ball_transform.translation.truncate(), BALL_DIAMETER / 2.),
-                ball_transform.translation.truncate(),
-                &Aabb2d::new(
-                    player_transform.translation.truncate(),
-                    player_transform.scale.truncate() / 2.,
-                ),
-            );
+        commands.entity(entity).with_children(|parent| {
+            // One ghost for the left or right edge, one for the top or bottom edge,
+            // and one for the opposite corner
+            for axes in [
+                BVec2::new(true, false),
+                BVec2::new(false, true),
+                BVec2::TRUE,
+            ] {
+                parent.spawn((
+                    mesh.clone(),
+                    material.clone(),
+                    Transform::default(),
+                    Visibility::Hidden,
+                    WrapGhost { axes },
+                ));
+            }
+        });
+    }
+}
 
-            if collision.is_some() {
-                // Writes a collision event so that other systems can react to the collision
-                ball_collision_events.write_default();
+// Shows the ghosts of the wrapping entities that straddle an edge, on the opposite side
+pub fn update_wrap_ghosts(
+    q_window_primary: Query<&Window, With<PrimaryWindow>>,
+    q_window: Query<&Window, Without<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &Transform), (With<Camera>, Without<WrapGhost>)>,
+    q_wrapping: Query<
+        (&Transform, &Shape, &Children, Has<EnteringWindow>),
+        (Without<Camera>, Without<WrapGhost>),
+    >,
+    mut q_ghost: Query<(&WrapGhost, &mut Transform, &mut Visibility)>,
+) {
+    for (camera, camera_transform) in &camera_query {
+        let Some(view) = camera_view(camera, camera_transform, &q_window_primary, &q_window) else {
+            continue;
+        };
+        let size = view.size();
+
+        for (transform, shape, children, is_entering) in &q_wrapping {
+            let position = transform.translation.truncate();
+            let half_size = BoundingVolume::from_shape(shape, transform)
+                .aabb()
+                .half_size();
+
+            // Where the part of the entity that sticks out of the window must be drawn
+            let straddle = |position: f32, half_size: f32, min: f32, max: f32, size: f32| {
+                if position + half_size > max {
+                    -size
+                } else if position - half_size < min {
+                    size
+                } else {
+                    0.0
+                }
+            };
+            let offset = Vec2::new(
+                straddle(position.x, half_size.x, view.min.x, view.max.x, size.x),
+                straddle(position.y, half_size.y, view.min.y, view.max.y, size.y),
+            );
 
-                we_deh = true;
+            for child in children.iter() {
+                let Ok((ghost, mut ghost_transform, mut ghost_visibility)) = q_ghost.get_mut(child)
+                else {
+                    continue;
+                };
+
+                let shown = !is_entering
+                    && (!ghost.axes.x || offset.x != 0.0)
+                    && (!ghost.axes.y || offset.y != 0.0);
+
+                if shown {
+                    // Ghosts are children, so the offset is undone from the parent's rotation
+                    let world_offset = Vec2::select(ghost.axes, offset, Vec2::ZERO).extend(0.0);
+                    ghost_transform.translation = transform.rotation.inverse() * world_offset;
+                    *ghost_visibility = Visibility::Inherited;
+                } else {
+                    *ghost_visibility = Visibility::Hidden;
+                }
             }
         }
     }
+}
 
-    if we_deh {
-        for (ball_entity, _) in q_ball {
-            commands.entity(ball_entity).despawn();
-        }
+// Despawns the entities that have flown as far as they can
+pub fn limit_travel_distance(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut q_travel: Query<(Entity, &Velocity, &mut TravelDistance)>,
+) {
+    for (entity, velocity, mut travel_distance) in &mut q_travel {
+        travel_distance.a -= velocity.linvel.length() * time.delta_secs();
 
-        for (player_entity, _) in q_player {
-            commands.entity(player_entity).despawn();
+        // Bullets that flew out of the window or hit a ball this tick are gone already
+        if travel_distance.a <= 0.0 {
+            commands.entity(entity).try_despawn();
         }
+    }
+}
 
-        commands.spawn(PlayerBundle::new(&mut meshes, &mut materials));
+// Returns the entities of a collision that just started, ordered so that the first one
+// satisfies `is_first` and the second one `is_second`
+fn started_collision(
+    collision_event: &CollisionEvent,
+    is_first: impl Fn(Entity) -> bool,
+    is_second: impl Fn(Entity) -> bool,
+) -> Option<(Entity, Entity)> {
+    let CollisionEvent::Started(a, b, _) = *collision_event else {
+        return None;
+    };
+
+    if is_first(a) && is_second(b) {
+        Some((a, b))
+    } else if is_first(b) && is_second(a) {
+        Some((b, a))
+    } else {
+        None
     }
 }
 
-// * It must execute before normal collision detection, so that the bullets can be despawned
-pub fn check_for_bullet_collisions(
+pub fn check_for_ball_collisions(
     mut commands: Commands,
-    q_bullet: Query<(Entity, &Transform), With<Bullet>>,
-    q_collider: Query<(Entity, &Transform), (With<Ball>, Without<Bullet>, Without<Player>)>,
-    mut bullet_collision_events: EventWriter<BallCollisionEvent>,
+    mut rapier_collision_events: EventReader<CollisionEvent>,
+    q_ball: Query<(), (With<Ball>, Without<Player>)>,
+    mut q_player: Query<
+        (&mut Velocity, &mut Lives, &PlayerSlot, &Score),
+        (
+            With<Player>,
+            Without<Ball>,
+            Without<Respawning>,
+            Without<InvulnerableCooldown>,
+        ),
+    >,
+    mut final_score: ResMut<FinalScore>,
+    mut ball_collision_events: EventWriter<BallCollisionEvent>,
 ) {
-    for (bullet_entity, bullet_transform) in q_bullet {
-        for (ball_entity, collider_transform) in q_collider {
-            // TODO: add the boundingcircle (or alternative) to the bundles
-            let bullet_object = BoundingCircle::new(
-                bullet_transform.translation.truncate(),
-                bullet_transform.scale.x / 2.,
-            );
-            let bounding_box = Aabb2d::new(
-                collider_transform.translation.truncate(),
-                collider_transform.scale.truncate() / 2.,
-            );
+    let mut hit_players = EntityHashSet::default();
+
+    for collision_event in rapier_collision_events.read() {
+        let Some((player_entity, _)) = started_collision(
+            collision_event,
+            |entity| q_player.contains(entity),
+            |entity| q_ball.contains(entity),
+        ) else {
+            continue;
+        };
 
-            if bullet_object.intersects(&bounding_box) {
-                // Writes a collision event so that other systems can react to the collision
-                bullet_collision_events.write_default();
+        // A player hit this frame only becomes `Respawning` once the commands are applied,
+        // so several balls touching it at once must only take one life
+        if !hit_players.insert(player_entity) {
+            continue;
+        }
 
-                commands.entity(bullet_entity).despawn();
-                commands.entity(ball_entity).despawn();
-            }
+        let Ok((mut player_velocity, mut lives, slot, score)) = q_player.get_mut(player_entity)
+        else {
+            continue;
+        };
+
+        // Writes a collision event so that other systems can react to the collision
+        ball_collision_events.write_default();
+
+        lives.a = lives.a.saturating_sub(1);
+
+        if lives.a == 0 {
+            // The player is out, the run goes on as long as someone is left
+            retire_player(&mut commands, &mut final_score, player_entity, slot, score);
+            continue;
         }
+
+        // Take the player out of the game until it's safe to come back.
+        // Balls go through it until its invulnerability runs out.
+        player_velocity.linvel = Vec2::ZERO;
+        commands
+            .entity(player_entity)
+            .insert((
+                Respawning,
+                Visibility::Hidden,
+                CollisionGroups::new(PLAYER_GROUP, Group::NONE),
+            ))
+            .remove::<IsBraking>();
     }
 }
 
-// * It must execute before normal collision detection, so that the bullets can be despawned
-pub fn check_for_laser_collisions(
+// Brings dead players back to their spawn point once no ball is close to it
+pub fn respawn_players(
     mut commands: Commands,
-    q_laser: Query<(&Transform, &Shape), With<Laser>>,
-    q_collider: Query<(Entity, &Transform), (With<Ball>, Without<Laser>, Without<Player>)>,
-    mut bullet_collision_events: EventWriter<BallCollisionEvent>,
+    config: Res<AsteroidsConfig>,
+    q_ball: Query<&Transform, (With<Ball>, Without<Player>)>,
+    mut q_player: Query<
+        (Entity, &mut Transform, &mut Acceleration, &PlayerSlot),
+        (With<Player>, With<Respawning>),
+    >,
 ) {
-    for (laser_transform, laser_shape) in q_laser {
-        if let Shape::Rectangle(shape_rectangle) = laser_shape {
-            for (ball_entity, collider_transform) in q_collider {
-                let laser_object = Aabb2d::new(
-                    laser_transform.translation.truncate(),
-                    shape_rectangle.half_size,
-                );
+    for (player_entity, mut player_transform, mut acceleration, slot) in &mut q_player {
+        let spawn_point = slot.spawn_point(&config);
 
-                let bounding_box = Aabb2d::new(
-                    collider_transform.translation.truncate(),
-                    collider_transform.scale.truncate() / 2.,
-                );
+        let spawn_is_safe = q_ball.iter().all(|ball_transform| {
+            ball_transform.translation.truncate().distance(spawn_point)
+                > config.player_safe_spawn_radius
+        });
 
-                if laser_object.intersects(&bounding_box) {
-                    // Writes a collision event so that other systems can react to the collision
-                    bullet_collision_events.write_default();
-
-                    commands.entity(ball_entity).despawn();
-                }
-            }
+        if !spawn_is_safe {
+            continue;
         }
+
+        player_transform.translation = spawn_point.extend(0.0);
+        player_transform.rotation =
+            Quat::from_rotation_z(PLAYER_STARTING_ORIENTATION.angle_to(Vec2::X));
+        acceleration.a = PLAYER_STARTING_ACCELERATION;
+
+        commands
+            .entity(player_entity)
+            .remove::<Respawning>()
+            .insert((
+                InvulnerableCooldown::new(config.player_invulnerable_time),
+                Visibility::Inherited,
+            ));
     }
 }
 
-// Returns `Some` if `ball` collides with `bounding_box`.
-// The returned `Collision` is the side of `bounding_box` that `ball` hit.
-fn collision(
-    object: impl IntersectsVolume<Aabb2d>,
-    object_center: Vec2,
-    bounding_box: &Aabb2d,
-) -> Option<Collision> {
-    if !object.intersects(bounding_box) {
-        return None;
-    }
+// Makes invulnerable players blink, and makes them vulnerable again when the time runs out
+pub fn update_invulnerability(
+    mut commands: Commands,
+    config: Res<AsteroidsConfig>,
+    mut q_player: Query<(Entity, &InvulnerableCooldown, &mut Visibility), With<Player>>,
+) {
+    for (player_entity, invulnerable_cooldown, mut visibility) in &mut q_player {
+        if invulnerable_cooldown.finished() {
+            *visibility = Visibility::Inherited;
+            commands
+                .entity(player_entity)
+                .remove::<InvulnerableCooldown>()
+                .insert(CollisionGroups::new(PLAYER_GROUP, PLAYER_FILTER));
+            continue;
+        }
 
-    let closest = bounding_box.closest_point(object_center);
-    let offset = object_center - closest;
-    let side = if offset.x.abs() > offset.y.abs() {
-        if offset.x < 0. {
-            Collision::Left
+        let blink =
+            (invulnerable_cooldown.elapsed_secs() / config.player_invulnerable_blink_time) as u32;
+        *visibility = if blink.is_multiple_of(2) {
+            Visibility::Hidden
         } else {
-            Collision::Right
+            Visibility::Inherited
+        };
+    }
+}
+
+// Destroys a hit ball, spawning the two halves of the next size tier where it was.
+// Returns the points the hit is worth.
+fn split_ball(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    config: &AsteroidsConfig,
+    ball_entity: Entity,
+    (ball_transform, ball_velocity, ball_size, ball_speed, ball_behaviour): (
+        &Transform,
+        &Velocity,
+        &BallSize,
+        &Speed,
+        &BallBehaviour,
+    ),
+) -> usize {
+    commands.entity(ball_entity).despawn();
+
+    if let Some(child_size) = ball_size.split() {
+        let heading = ball_velocity.linvel.try_normalize().unwrap_or(Vec2::X);
+
+        for angle in [config.ball_split_angle, -config.ball_split_angle] {
+            let child_velocity = Vec2::from_angle(angle).rotate(heading) * ball_speed.a;
+
+            commands.spawn(BallBundle::new(
+                meshes,
+                materials,
+                config,
+                ball_transform.translation.truncate(),
+                child_size,
+                child_velocity,
+                ball_speed.a,
+                *ball_behaviour,
+            ));
         }
-    } else if offset.y > 0. {
-        Collision::Top
-    } else {
-        Collision::Bottom
-    };
+    }
 
-    Some(side)
+    ball_size.score(config)
 }
 
-pub fn play_collision_sound(
+pub fn check_for_bullet_collisions(
     mut commands: Commands,
-    mut collision_events: EventReader<BallCollisionEvent>,
-    sound: Res<CollisionSound>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    config: Res<AsteroidsConfig>,
+    mut rapier_collision_events: EventReader<CollisionEvent>,
+    q_bullet: Query<&GeneratedBy, With<Bullet>>,
+    q_ball: Query<
+        (&Transform, &Velocity, &BallSize, &Speed, &BallBehaviour),
+        (With<Ball>, Without<Bullet>),
+    >,
+    mut q_score: Query<&mut Score, With<Player>>,
+    mut bullet_collision_events: EventWriter<BallCollisionEvent>,
 ) {
-    // Play a sound once per frame if a collision occurred.
-    if !collision_events.is_empty() {
-        // This prevents events staying active on the next frame.
-        collision_events.clear();
-        commands.spawn((AudioPlayer(sound.clone()), PlaybackSettings::DESPAWN));
+    // Entities destroyed this frame, so two bullets can't be credited for the same ball
+    // and a bullet can't destroy two balls
+    let mut destroyed = EntityHashSet::default();
+
+    for collision_event in rapier_collision_events.read() {
+        let Some((bullet_entity, ball_entity)) = started_collision(
+            collision_event,
+            |entity| q_bullet.contains(entity),
+            |entity| q_ball.contains(entity),
+        ) else {
+            continue;
+        };
+
+        if destroyed.contains(&bullet_entity) || destroyed.contains(&ball_entity) {
+            continue;
+        }
+
+        // Writes a collision event so that other systems can react to the collision
+        bullet_collision_events.write_default();
+
+        commands.entity(bullet_entity).try_despawn();
+        destroyed.insert(bullet_entity);
+        destroyed.insert(ball_entity);
+
+        let Ok(ball) = q_ball.get(ball_entity) else {
+            continue;
+        };
+        let points = split_ball(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            &config,
+            ball_entity,
+            ball,
+        );
+
+        // The player that fired the bullet gets the points
+        if let Ok(generated_by) = q_bullet.get(bullet_entity)
+            && let Ok(mut score) = q_score.get_mut(generated_by.0)
+        {
+            score.a += points;
+        }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
-enum Collision {
-    Left,
-    Right,
-    Top,
-    Bottom,
+// The beam hits the first ball along its length, and stops there
+pub fn check_for_laser_collisions(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    config: Res<AsteroidsConfig>,
+    rapier_context: ReadRapierContext,
+    mut q_laser: Query<(&AnchorTo, &GeneratedBy, &mut Transform), With<Laser>>,
+    q_player: Query<&Transform, (With<Player>, Without<Laser>)>,
+    q_ball: Query<
+        (&Transform, &Velocity, &BallSize, &Speed, &BallBehaviour),
+        (With<Ball>, Without<Laser>),
+    >,
+    mut q_score: Query<&mut Score, With<Player>>,
+    mut bullet_collision_events: EventWriter<BallCollisionEvent>,
+) {
+    let Ok(rapier_context) = rapier_context.single() else {
+        return;
+    };
+
+    let filter = QueryFilter::new().groups(CollisionGroups::new(LASER_GROUP, LASER_FILTER));
+
+    // Balls destroyed this frame, so crossing beams can't be credited for the same ball
+    let mut destroyed_balls = EntityHashSet::default();
+
+    for (anchor_to, generated_by, mut laser_transform) in &mut q_laser {
+        let Ok(player_transform) = q_player.get(anchor_to.target) else {
+            continue;
+        };
+
+        let origin = player_transform.translation.truncate();
+        let direction = player_transform.rotation.mul_vec3(Vec3::X).truncate();
+
+        let Some((ball_entity, distance)) =
+            rapier_context.cast_ray(origin, direction, config.laser_length, true, filter)
+        else {
+            continue;
+        };
+
+        let Ok(ball) = q_ball.get(ball_entity) else {
+            continue;
+        };
+        if !destroyed_balls.insert(ball_entity) {
+            continue;
+        }
+
+        // Cut the beam where it hits the ball
+        laser_transform.scale.x = distance / config.laser_length;
+        laser_transform.translation =
+            (origin + direction * distance / 2.0).extend(laser_transform.translation.z);
+
+        // Writes a collision event so that other systems can react to the collision
+        bullet_collision_events.write_default();
+
+        let points = split_ball(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            &config,
+            ball_entity,
+            ball,
+        );
+
+        // The player that fired the laser gets the points
+        if let Ok(mut score) = q_score.get_mut(generated_by.0) {
+            score.a += points;
+        }
+    }
 }
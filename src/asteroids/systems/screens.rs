@@ -0,0 +1,107 @@
+use super::super::components::PlayerSlot;
+use super::super::resources::*;
+use super::super::states::*;
+use bevy::prelude::*;
+use bevy_sandbox::input::*;
+use bevy_sandbox::screen::ScreenStyle;
+
+pub fn spawn_menu_screen(
+    mut commands: Commands,
+    style: Res<ScreenStyle>,
+    bindings: Res<InputBindings>,
+) {
+    style.spawn(
+        &mut commands,
+        GameState::Menu,
+        "Asteroids",
+        &[
+            format!("Press {} to play", bindings.describe(ButtonAction::Confirm)),
+            format!(
+                "Press {} to change the controls",
+                bindings.describe(ButtonAction::Settings)
+            ),
+        ],
+    );
+}
+
+pub fn spawn_pause_screen(
+    mut commands: Commands,
+    style: Res<ScreenStyle>,
+    bindings: Res<InputBindings>,
+) {
+    style.spawn(
+        &mut commands,
+        GameState::Paused,
+        "Paused",
+        &[format!(
+            "Press {} to resume",
+            bindings.describe(ButtonAction::Pause)
+        )],
+    );
+}
+
+pub fn spawn_game_over_screen(
+    mut commands: Commands,
+    style: Res<ScreenStyle>,
+    final_score: Res<FinalScore>,
+    bindings: Res<InputBindings>,
+) {
+    let mut lines: Vec<String> = final_score
+        .iter()
+        .map(|(slot, score)| format!("{}: {score}", PlayerSlot { a: *slot }.name()))
+        .collect();
+    lines.push(format!(
+        "Press {} to play again",
+        bindings.describe(ButtonAction::Confirm)
+    ));
+    lines.push(format!(
+        "Press {} to go back to the menu",
+        bindings.describe(ButtonAction::Back)
+    ));
+
+    style.spawn(&mut commands, GameState::GameOver, "Game Over", &lines);
+}
+
+pub fn menu_input(action_input: ActionInput, mut next_state: ResMut<NextState<GameState>>) {
+    if action_input.just_pressed(ButtonAction::Confirm) {
+        next_state.set(GameState::Playing);
+    } else if action_input.just_pressed(ButtonAction::Settings) {
+        next_state.set(GameState::Settings);
+    }
+}
+
+// A replay starts its run straight away, the recording's run is all it has inputs for
+pub fn start_replay(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Playing);
+}
+
+pub fn toggle_pause(
+    action_input: ActionInput,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if action_input.just_pressed(ButtonAction::Pause) {
+        match state.get() {
+            GameState::Playing => next_state.set(GameState::Paused),
+            GameState::Paused => next_state.set(GameState::Playing),
+            GameState::Menu | GameState::Settings | GameState::GameOver => {}
+        }
+    }
+}
+
+pub fn game_over_input(action_input: ActionInput, mut next_state: ResMut<NextState<GameState>>) {
+    if action_input.just_pressed(ButtonAction::Confirm) {
+        next_state.set(GameState::Playing);
+    } else if action_input.just_pressed(ButtonAction::Back) {
+        next_state.set(GameState::Menu);
+    }
+}
+
+// Freezing the virtual clock stops both `FixedUpdate` and the physics step
+pub fn pause_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+pub fn unpause_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
@@ -0,0 +1,136 @@
+// Whole game tests, run headless one fixed tick at a time
+
+use super::*;
+use bevy_sandbox::collision::Shape;
+use bevy_sandbox::headless::*;
+
+// A run of the game with the default controls, and no waves so the tests bring their own balls
+fn start_run() -> App {
+    let mut app = headless_app();
+    app.add_plugins(AsteroidsPlugin);
+
+    step_until_config_ready::<AsteroidsConfig>(&mut app);
+
+    let world = app.world_mut();
+    world.resource_mut::<AsteroidsConfig>().wave_spawn_cooldown = 1.0e6;
+    *world.resource_mut::<InputBindings>() = InputBindings::default();
+    world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Playing);
+    app.update();
+
+    app
+}
+
+fn keyboard_player(app: &mut App) -> (Entity, Transform) {
+    let world = app.world_mut();
+    let mut q_player = world.query_filtered::<(Entity, &Transform, &ControlledBy), With<Player>>();
+
+    q_player
+        .iter(world)
+        .find(|(_, _, controlled_by)| controlled_by.a == InputSource::Keyboard)
+        .map(|(entity, transform, _)| (entity, *transform))
+        .expect("the keyboard player spawns with the run")
+}
+
+fn spawn_still_ball(app: &mut App, position: Vec2) -> Entity {
+    let config = app.world().resource::<AsteroidsConfig>().clone();
+    spawn_with_assets(app, move |meshes, materials| {
+        BallBundle::new(
+            meshes,
+            materials,
+            &config,
+            position,
+            BallSize::Large,
+            Vec2::ZERO,
+            0.0,
+            BallBehaviour::Drift,
+        )
+    })
+}
+
+#[test]
+fn bullet_destroys_a_ball_and_scores() {
+    let mut app = start_run();
+    let (player, transform) = keyboard_player(&mut app);
+    let facing = transform.rotation.mul_vec3(Vec3::X).truncate();
+    let ball = spawn_still_ball(&mut app, transform.translation.truncate() + facing * 200.0);
+
+    press(&mut app, KeyCode::Space);
+    let scored = step_until(&mut app, 120, |world| {
+        world.get::<Score>(player).unwrap().a > 0
+    });
+
+    assert!(scored, "the bullet never scored");
+    assert!(app.world().get_entity(ball).is_err());
+}
+
+#[test]
+fn player_cannot_leave_the_arena() {
+    let mut app = start_run();
+    let (player, start) = keyboard_player(&mut app);
+    let half_size = HEADLESS_WINDOW_SIZE / 2.0;
+    let mut wrapped = false;
+
+    press(&mut app, KeyCode::ArrowRight);
+    for _ in 0..600 {
+        app.update();
+        let position = app.world().get::<Transform>(player).unwrap().translation;
+
+        assert!(
+            position.x.abs() <= half_size.x && position.y.abs() <= half_size.y,
+            "the player left the arena at {position}"
+        );
+        wrapped |= position.x < start.translation.x - 1.0;
+    }
+
+    assert!(wrapped, "the player never reached the edge");
+}
+
+#[test]
+fn reloaded_sizes_and_colors_restyle_the_game() {
+    let mut app = start_run();
+    let ball = spawn_still_ball(&mut app, Vec2::splat(300.0));
+    let mut config = app.world_mut().resource_mut::<AsteroidsConfig>();
+    config.ball_large_diameter = BALL_LARGE_DIAMETER * 2.0;
+    config.ball_color = Color::BLACK;
+    app.update();
+
+    let world = app.world();
+    let Some(Shape::Circle(circle)) = world.get::<Shape>(ball) else {
+        panic!("balls are circles");
+    };
+    assert_eq!(circle.radius, BALL_LARGE_DIAMETER);
+    let collider = world.get::<Collider>(ball).unwrap();
+    assert_eq!(collider.as_ball().unwrap().radius(), BALL_LARGE_DIAMETER);
+
+    let material = world.get::<MeshMaterial2d<ColorMaterial>>(ball).unwrap();
+    let materials = world.resource::<Assets<ColorMaterial>>();
+    assert_eq!(materials.get(material).unwrap().color, Color::BLACK);
+}
+
+#[test]
+fn back_cancels_a_rebind() {
+    let mut app = headless_app();
+    app.add_plugins(AsteroidsPlugin);
+    step_until_config_ready::<AsteroidsConfig>(&mut app);
+    *app.world_mut().resource_mut::<InputBindings>() = InputBindings::default();
+
+    tap(&mut app, KeyCode::Tab);
+    tap(&mut app, KeyCode::Enter);
+    assert!(
+        app.world()
+            .resource::<SettingsCursor<GameState>>()
+            .rebinding
+    );
+
+    tap(&mut app, KeyCode::Escape);
+
+    let world = app.world();
+    assert_eq!(
+        *world.resource::<State<GameState>>().get(),
+        GameState::Settings
+    );
+    assert!(!world.resource::<SettingsCursor<GameState>>().rebinding);
+    assert_eq!(*world.resource::<InputBindings>(), InputBindings::default());
+}
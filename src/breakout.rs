@@ -3,15 +3,47 @@
 //! Demonstrates Bevy's stepping capabilities if compiled with the `bevy_debug_stepping` feature.
 
 use bevy::{prelude::*, window::WindowMode};
+use bevy_sandbox::collision::*;
+use bevy_sandbox::collision_sound::*;
+use bevy_sandbox::config::*;
+use bevy_sandbox::cooldown::*;
+use bevy_sandbox::input::*;
+use bevy_sandbox::replay::*;
+use bevy_sandbox::rng::*;
+use bevy_sandbox::scoreboard::*;
+use bevy_sandbox::screen::*;
+use bevy_sandbox::settings::*;
+use bevy_sandbox::velocity::*;
+use components::*;
 use constants::*;
-use events::*;
+use levels::*;
 use resources::*;
+use states::*;
 use systems::*;
 
 pub mod constants {
     use bevy::prelude::*;
-
-    pub const SPAWN_BALLS: bool = false;
+    use bevy_sandbox::input::{AxisAction, ButtonAction};
+    use bevy_sandbox::settings::Control;
+
+    pub const INPUT_BINDINGS_PATH: &str = "config/breakout_input.ron"; // Where rebound controls are saved
+    // What the settings screen lets the player rebind. The thrust controls move the paddle.
+    pub const SETTINGS_CONTROLS: &[Control] = &[
+        Control::Keys(AxisAction::Thrust),
+        Control::Stick(AxisAction::Thrust),
+        Control::Button(ButtonAction::FireBullet),
+        Control::Button(ButtonAction::Confirm),
+        Control::Button(ButtonAction::Back),
+        Control::Button(ButtonAction::Settings),
+    ];
+    pub const CONFIG_PATH: &str = "config/breakout.ron"; // Inside the assets folder
+    pub const COLLISION_SOUND_PATH: &str = "sounds/breakout_collision.ogg";
+    // Played in this order, inside the assets folder
+    pub const LEVEL_PATHS: [&str; 3] = [
+        "levels/01_wall.level.ron",
+        "levels/02_pyramid.level.ron",
+        "levels/03_corridor.level.ron",
+    ];
 
     // These constants are defined in `Transform` units.
     // Using the default 2D camera they correspond 1:1 with screen pixels.
@@ -20,19 +52,42 @@ pub mod constants {
     pub const PADDLE_SPEED: f32 = 500.0;
     // How close can the paddle get to the wall
     pub const PADDLE_PADDING: f32 = 10.0;
+    // Angle from straight up, in degrees, of balls bouncing off the edges of the paddle.
+    // Balls bounce off the middle straight up, and in between in proportion.
+    pub const PADDLE_BOUNCE_MAX_ANGLE: f32 = 60.0;
 
     // We set the z-value of the ball to 1 so it renders on top in the case of overlapping sprites.
     pub const BALL_STARTING_POSITION: Vec3 = Vec3::new(0.0, -50.0, 1.0);
     pub const BALL_DIAMETER: f32 = 30.;
     pub const BALL_SPEED: f32 = 400.0;
     pub const BALL_SPEED_MULTIPLIER: f32 = 1.05; // Increase ball speed by 5% on each brick hit
+    pub const BALL_MAX_SPEED: f32 = 900.0; // Speed bricks don't push a ball past it
     pub const INITIAL_BALL_DIRECTION: Vec2 = Vec2::new(0.5, -0.5);
+    // Past this many bounces in a tick, a ball waits for the next tick to go further
+    pub const BALL_MAX_BOUNCES_PER_TICK: usize = 4;
+    pub const SERVE_DIRECTION: Vec2 = Vec2::new(0.5, 0.5);
+    pub const STARTING_LIVES: u32 = 3;
 
     pub const BULLET_DIAMETER: f32 = 10.;
     pub const BULLET_SPEED: f32 = 1000.0;
     pub const INITIAL_BULLET_DIRECTION: Vec2 = Vec2::Y;
     pub const BULLET_COOLDOWN: f32 = 0.05; // Time in seconds before the next bullet can be fired
 
+    pub const CAPSULE_SIZE: Vec2 = Vec2::new(40.0, 15.0);
+    pub const CAPSULE_DROP_CHANCE: f32 = 0.15; // Of each broken brick
+    pub const CAPSULE_FALL_SPEED: f32 = 200.0;
+    // WidePaddle, StickyPaddle, SlowBall, LaserPaddle, MultiBall
+    pub const POWER_UP_WIDE_PADDLE_WEIGHT: f32 = 0.25;
+    pub const POWER_UP_STICKY_PADDLE_WEIGHT: f32 = 0.2;
+    pub const POWER_UP_SLOW_BALL_WEIGHT: f32 = 0.2;
+    pub const POWER_UP_LASER_PADDLE_WEIGHT: f32 = 0.2;
+    pub const POWER_UP_MULTI_BALL_WEIGHT: f32 = 0.15;
+    pub const POWER_UP_DURATION: f32 = 10.0; // Of the power-ups that wear off
+    pub const WIDE_PADDLE_MULTIPLIER: f32 = 1.5;
+    pub const SLOW_BALL_MULTIPLIER: f32 = 0.6;
+    // Angle in degrees between a ball and each of the two balls a multi-ball splits off it
+    pub const MULTI_BALL_SPREAD: f32 = 20.0;
+
     pub const WALL_THICKNESS: f32 = 10.0;
     // x coordinates
     pub const LEFT_WALL: f32 = -800.;
@@ -42,10 +97,24 @@ pub mod constants {
     pub const TOP_WALL: f32 = 400.;
 
     pub const BRICK_SIZE: Vec2 = Vec2::new(100., 30.);
-    // Normal, Speed, ExtraBall
-    pub const BRICK_TYPE_NORMAL_WEIGHT: f32 = 0.7;
-    pub const BRICK_TYPE_SPEED_WEIGHT: f32 = 0.2;
-    pub const BRICK_TYPE_EXTRA_BALL_WEIGHT: f32 = 0.1;
+    // Normal, Speed, ExtraBall, Tough, Indestructible, Explosive, PaddleGrow, PaddleShrink
+    pub const BRICK_TYPE_NORMAL_WEIGHT: f32 = 0.6;
+    pub const BRICK_TYPE_SPEED_WEIGHT: f32 = 0.15;
+    pub const BRICK_TYPE_EXTRA_BALL_WEIGHT: f32 = 0.08;
+    pub const BRICK_TYPE_TOUGH_WEIGHT: f32 = 0.07;
+    pub const BRICK_TYPE_INDESTRUCTIBLE_WEIGHT: f32 = 0.0;
+    pub const BRICK_TYPE_EXPLOSIVE_WEIGHT: f32 = 0.05;
+    pub const BRICK_TYPE_PADDLE_GROW_WEIGHT: f32 = 0.03;
+    pub const BRICK_TYPE_PADDLE_SHRINK_WEIGHT: f32 = 0.02;
+    pub const TOUGH_BRICK_HIT_POINTS: u32 = 3;
+    // Bricks with their centers this close to a breaking explosive brick break too
+    pub const EXPLOSION_RADIUS: f32 = 120.0;
+    // The paddle grows by this much, or shrinks by as much, between these widths
+    pub const PADDLE_RESIZE_MULTIPLIER: f32 = 1.25;
+    pub const PADDLE_MIN_WIDTH: f32 = 60.0;
+    pub const PADDLE_MAX_WIDTH: f32 = 240.0;
+    // Each hit point over one makes a brick this much darker
+    pub const BRICK_DAMAGE_DARKENING: f32 = 0.12;
 
     // These values are exact
     pub const GAP_BETWEEN_PADDLE_AND_BRICKS: f32 = 270.0;
@@ -57,6 +126,10 @@ pub mod constants {
     pub const SCOREBOARD_FONT_SIZE: f32 = 33.0;
     pub const SCOREBOARD_TEXT_PADDING: Val = Val::Px(5.0);
 
+    pub const TITLE_FONT_SIZE: f32 = 80.0;
+    pub const SUBTITLE_FONT_SIZE: f32 = 33.0;
+    pub const SCREEN_ROW_GAP: Val = Val::Px(20.0);
+
     pub const BACKGROUND_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
     pub const PADDLE_COLOR: Color = Color::srgb(0.3, 0.3, 0.7);
     pub const BALL_COLOR: Color = Color::srgb(1.0, 0.5, 0.5);
@@ -64,19 +137,64 @@ pub mod constants {
     pub const BRICK_NORMAL_COLOR: Color = Color::srgb(0.5, 0.5, 1.0);
     pub const BRICK_SPEED_COLOR: Color = Color::srgb(0.5, 1.0, 0.5);
     pub const BRICK_EXTRA_BALL_COLOR: Color = Color::srgb(1.0, 1.0, 0.5);
+    pub const BRICK_TOUGH_COLOR: Color = Color::srgb(0.7, 0.7, 1.0);
+    pub const BRICK_INDESTRUCTIBLE_COLOR: Color = Color::srgb(0.45, 0.45, 0.45);
+    pub const BRICK_EXPLOSIVE_COLOR: Color = Color::srgb(1.0, 0.6, 0.3);
+    pub const BRICK_PADDLE_GROW_COLOR: Color = Color::srgb(0.5, 1.0, 1.0);
+    pub const BRICK_PADDLE_SHRINK_COLOR: Color = Color::srgb(1.0, 0.5, 1.0);
+    pub const WIDE_PADDLE_COLOR: Color = Color::srgb(0.3, 0.7, 0.9);
+    pub const STICKY_PADDLE_COLOR: Color = Color::srgb(0.6, 0.8, 0.2);
+    pub const SLOW_BALL_COLOR: Color = Color::srgb(0.9, 0.7, 0.2);
+    pub const LASER_PADDLE_COLOR: Color = Color::srgb(0.9, 0.2, 0.2);
+    pub const MULTI_BALL_COLOR: Color = Color::srgb(0.8, 0.4, 0.9);
     pub const WALL_COLOR: Color = Color::srgb(0.8, 0.8, 0.8);
     pub const TEXT_COLOR: Color = Color::srgb(0.5, 0.5, 1.0);
     pub const SCORE_COLOR: Color = Color::srgb(1.0, 0.5, 0.5);
+    pub const TITLE_COLOR: Color = Color::srgb(0.3, 0.3, 0.7);
+    pub const SCREEN_OVERLAY_COLOR: Color = Color::srgba(0.9, 0.9, 0.9, 0.7);
+}
+
+pub mod states {
+    use bevy::prelude::*;
+
+    // The bricks, balls and bullets of a game are scoped to `Playing`,
+    // so the next game or level starts from a clean field
+    #[derive(States, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    #[states(scoped_entities)]
+    pub enum GameState {
+        // Waiting for the config
+        #[default]
+        Loading,
+        Playing,
+        // Between two levels
+        LevelCleared,
+        GameOver,
+        Victory,
+        // Rebinding the controls, opened from the three screens above
+        Settings,
+    }
 }
 
 pub mod components {
     use super::constants::*;
+    use super::levels::Arena;
+    use super::resources::BreakoutConfig;
     use bevy::prelude::*;
+    use bevy_sandbox::collision::Shape;
+    use bevy_sandbox::cooldown::Cooldown;
+    use bevy_sandbox::rng::GameRng;
     use std::time::Duration;
 
     #[derive(Component)]
     pub struct Paddle;
 
+    // Width of the paddle without the wide paddle power-up, which bricks grow and shrink,
+    // as a multiple of the width in the config
+    #[derive(Component)]
+    pub struct PaddleWidth {
+        pub a: f32,
+    }
+
     #[derive(Component)]
     pub struct BulletCooldown {
         a: Timer,
@@ -87,10 +205,6 @@ pub mod components {
             self.a.reset();
         }
 
-        pub fn tick(&mut self, delta: Duration) {
-            self.a.tick(delta);
-        }
-
         pub fn elapsed_secs(&self) -> f32 {
             self.a.elapsed_secs()
         }
@@ -98,6 +212,10 @@ pub mod components {
         pub fn finished(&self) -> bool {
             self.a.finished()
         }
+
+        pub fn set_duration(&mut self, seconds: f32) {
+            self.a.set_duration(Duration::from_secs_f32(seconds));
+        }
     }
 
     impl Default for BulletCooldown {
@@ -108,71 +226,228 @@ pub mod components {
         }
     }
 
+    impl Cooldown for BulletCooldown {
+        fn timer_mut(&mut self) -> &mut Timer {
+            &mut self.a
+        }
+    }
+
+    // How fast the entity moves, its velocity is kept this long
+    #[derive(Component)]
+    pub struct Speed {
+        pub a: f32,
+    }
+
     #[derive(Component)]
     pub struct RoundCollisionable;
 
     #[derive(Component)]
     pub struct Ball;
 
+    // A ball resting on the paddle, waiting to be served. It's held `offset` right of
+    // the middle of the paddle, and leaves in `direction`.
     #[derive(Component)]
-    pub struct Bullet;
+    pub struct Served {
+        pub offset: f32,
+        pub direction: Vec2,
+    }
+
+    impl Default for Served {
+        fn default() -> Self {
+            Served {
+                offset: 0.0,
+                direction: SERVE_DIRECTION.normalize(),
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    pub enum PowerUp {
+        WidePaddle,
+        // Balls stick to the paddle and wait to be launched again
+        StickyPaddle,
+        SlowBall,
+        // The paddle fires bullets
+        LaserPaddle,
+        // Every ball splits in three, at once
+        MultiBall,
+    }
+
+    impl PowerUp {
+        pub const ALL: [PowerUp; 5] = [
+            PowerUp::WidePaddle,
+            PowerUp::StickyPaddle,
+            PowerUp::SlowBall,
+            PowerUp::LaserPaddle,
+            PowerUp::MultiBall,
+        ];
+
+        pub fn color(&self, config: &BreakoutConfig) -> Color {
+            match self {
+                PowerUp::WidePaddle => config.wide_paddle_color,
+                PowerUp::StickyPaddle => config.sticky_paddle_color,
+                PowerUp::SlowBall => config.slow_ball_color,
+                PowerUp::LaserPaddle => config.laser_paddle_color,
+                PowerUp::MultiBall => config.multi_ball_color,
+            }
+        }
+
+        pub fn name(&self) -> &'static str {
+            match self {
+                PowerUp::WidePaddle => "Wide",
+                PowerUp::StickyPaddle => "Sticky",
+                PowerUp::SlowBall => "Slow",
+                PowerUp::LaserPaddle => "Laser",
+                PowerUp::MultiBall => "Multi-ball",
+            }
+        }
+
+        // Whether it lasts for a while, instead of being over once it's caught
+        pub fn is_timed(&self) -> bool {
+            *self != PowerUp::MultiBall
+        }
+
+        pub fn weights(config: &BreakoutConfig) -> Vec<f32> {
+            Vec::from([
+                config.power_up_wide_paddle_weight,
+                config.power_up_sticky_paddle_weight,
+                config.power_up_slow_ball_weight,
+                config.power_up_laser_paddle_weight,
+                config.power_up_multi_ball_weight,
+            ])
+        }
 
-    #[derive(Component, Deref, DerefMut)]
-    pub struct Velocity(pub Vec2);
+        pub fn random(config: &BreakoutConfig, rng: &mut GameRng) -> Self {
+            match rng.weighted_index(&Self::weights(config)) {
+                Some(1) => PowerUp::StickyPaddle,
+                Some(2) => PowerUp::SlowBall,
+                Some(3) => PowerUp::LaserPaddle,
+                Some(4) => PowerUp::MultiBall,
+                _ => PowerUp::WidePaddle,
+            }
+        }
+    }
+
+    // Falls from a broken brick, and gives its power-up to the paddle that catches it
+    #[derive(Component)]
+    pub struct Capsule {
+        pub power_up: PowerUp,
+    }
+
+    #[derive(Component)]
+    pub struct Bullet;
 
+    #[derive(Default, Clone, Copy, PartialEq, Debug)]
     pub enum BrickType {
+        #[default]
         Normal,
         Speed,
         ExtraBall,
+        // Takes several hits
+        Tough,
+        // Never breaks, and doesn't need to for the level to be cleared
+        Indestructible,
+        // Breaks the bricks around it when it breaks
+        Explosive,
+        PaddleGrow,
+        PaddleShrink,
     }
 
     impl BrickType {
-        pub fn color(&self) -> Color {
+        pub fn color(&self, config: &BreakoutConfig) -> Color {
             match self {
-                BrickType::Normal => BRICK_NORMAL_COLOR,
-                BrickType::Speed => BRICK_SPEED_COLOR,
-                BrickType::ExtraBall => BRICK_EXTRA_BALL_COLOR,
+                BrickType::Normal => config.brick_normal_color,
+                BrickType::Speed => config.brick_speed_color,
+                BrickType::ExtraBall => config.brick_extra_ball_color,
+                BrickType::Tough => config.brick_tough_color,
+                BrickType::Indestructible => config.brick_indestructible_color,
+                BrickType::Explosive => config.brick_explosive_color,
+                BrickType::PaddleGrow => config.brick_paddle_grow_color,
+                BrickType::PaddleShrink => config.brick_paddle_shrink_color,
             }
         }
 
-        pub fn weights() -> Vec<f32> {
-            Vec::from([
-                BRICK_TYPE_NORMAL_WEIGHT,
-                BRICK_TYPE_SPEED_WEIGHT,
-                BRICK_TYPE_EXTRA_BALL_WEIGHT,
-            ])
+        // How the type is written in level files
+        pub fn from_letter(letter: char) -> Option<Self> {
+            match letter {
+                'N' => Some(BrickType::Normal),
+                'S' => Some(BrickType::Speed),
+                'E' => Some(BrickType::ExtraBall),
+                'T' => Some(BrickType::Tough),
+                'I' => Some(BrickType::Indestructible),
+                'X' => Some(BrickType::Explosive),
+                'G' => Some(BrickType::PaddleGrow),
+                'K' => Some(BrickType::PaddleShrink),
+                _ => None,
+            }
         }
 
-        pub fn random() -> Self {
-            let random = getrandom::u32().unwrap_or(0);
-            let weights = Self::weights();
-            let total_weight: f32 = weights.iter().sum();
-            let mut cumulative_weight = 0.0;
-
-            for (i, &weight) in weights.iter().enumerate() {
-                cumulative_weight += weight / total_weight;
-                if (random as f32) / (u32::MAX as f32) < cumulative_weight {
-                    return match i {
-                        0 => BrickType::Normal,
-                        1 => BrickType::Speed,
-                        _ => BrickType::ExtraBall,
-                    };
-                }
+        // Hits a brick of this type takes when the level doesn't say
+        pub fn hit_points(&self, config: &BreakoutConfig) -> u32 {
+            match self {
+                BrickType::Tough => config.tough_brick_hit_points,
+                _ => 1,
             }
+        }
 
-            BrickType::Normal // Fallback, should not happen
+        pub fn weights(config: &BreakoutConfig) -> Vec<f32> {
+            Vec::from([
+                config.brick_type_normal_weight,
+                config.brick_type_speed_weight,
+                config.brick_type_extra_ball_weight,
+                config.brick_type_tough_weight,
+                config.brick_type_indestructible_weight,
+                config.brick_type_explosive_weight,
+                config.brick_type_paddle_grow_weight,
+                config.brick_type_paddle_shrink_weight,
+            ])
         }
-    }
 
-    impl Default for BrickType {
-        fn default() -> Self {
-            BrickType::Normal
+        pub fn random(config: &BreakoutConfig, rng: &mut GameRng) -> Self {
+            match rng.weighted_index(&Self::weights(config)) {
+                Some(1) => BrickType::Speed,
+                Some(2) => BrickType::ExtraBall,
+                Some(3) => BrickType::Tough,
+                Some(4) => BrickType::Indestructible,
+                Some(5) => BrickType::Explosive,
+                Some(6) => BrickType::PaddleGrow,
+                Some(7) => BrickType::PaddleShrink,
+                _ => BrickType::Normal,
+            }
         }
     }
 
     #[derive(Component)]
     pub struct Brick {
         pub r#type: BrickType,
+        // Hits left before the brick breaks
+        pub hit_points: u32,
+    }
+
+    impl Brick {
+        // Darker the more hits it has left, so it lightens as it takes damage
+        pub fn color(&self, config: &BreakoutConfig) -> Color {
+            let extra_hit_points = self.hit_points.saturating_sub(1) as f32;
+            self.r#type
+                .color(config)
+                .darker(config.brick_damage_darkening * extra_hit_points)
+        }
+
+        // Takes a hit point off the brick, whether that broke it
+        pub fn hit(&mut self) -> bool {
+            if self.r#type == BrickType::Indestructible {
+                return false;
+            }
+            self.hit_points = self.hit_points.saturating_sub(1);
+            self.hit_points == 0
+        }
+    }
+
+    // A brick that broke this frame, and is yet to go off, with the ball that broke it
+    // if it was one
+    #[derive(Component)]
+    pub struct Broken {
+        pub by: Option<Entity>,
     }
 
     // Default must be implemented to define this as a required component for the Wall component below
@@ -184,7 +459,21 @@ pub mod components {
     #[require(Sprite, Transform, Collider)]
     pub struct Wall;
 
+    // The wall behind the paddle, balls that reach it are lost
+    #[derive(Component)]
+    pub struct KillZone;
+
+    // A ball that reached the kill zone this tick, and is yet to be taken out of play
+    #[derive(Component)]
+    pub struct Lost;
+
+    // Sprites are one unit across and scaled to size, so are the shapes hitting them
+    pub fn unit_square() -> Shape {
+        Shape::Rectangle(Rectangle::new(1.0, 1.0))
+    }
+
     /// Which side of the arena is this wall located on?
+    #[derive(Component, Clone, Copy)]
     pub enum WallLocation {
         Left,
         Right,
@@ -194,19 +483,20 @@ pub mod components {
 
     impl WallLocation {
         /// Location of the *center* of the wall, used in `transform.translation()`
-        fn position(&self) -> Vec2 {
+        pub fn position(&self, arena: &Arena) -> Vec2 {
+            let center = arena.center();
             match self {
-                WallLocation::Left => Vec2::new(LEFT_WALL, (TOP_WALL + BOTTOM_WALL) / 2.0),
-                WallLocation::Right => Vec2::new(RIGHT_WALL, (TOP_WALL + BOTTOM_WALL) / 2.0),
-                WallLocation::Bottom => Vec2::new((RIGHT_WALL + LEFT_WALL) / 2.0, BOTTOM_WALL),
-                WallLocation::Top => Vec2::new((RIGHT_WALL + LEFT_WALL) / 2.0, TOP_WALL),
+                WallLocation::Left => Vec2::new(arena.left, center.y),
+                WallLocation::Right => Vec2::new(arena.right, center.y),
+                WallLocation::Bottom => Vec2::new(center.x, arena.bottom),
+                WallLocation::Top => Vec2::new(center.x, arena.top),
             }
         }
 
         /// (x, y) dimensions of the wall, used in `transform.scale()`
-        fn size(&self) -> Vec2 {
-            let arena_height = TOP_WALL - BOTTOM_WALL;
-            let arena_width = RIGHT_WALL - LEFT_WALL;
+        pub fn size(&self, arena: &Arena) -> Vec2 {
+            let arena_height = arena.height();
+            let arena_width = arena.width();
             // Make sure we haven't messed up our constants
             assert!(arena_height > 0.0);
             assert!(arena_width > 0.0);
@@ -226,58 +516,68 @@ pub mod components {
         // This "builder method" allows us to reuse logic across our wall entities,
         // making our code easier to read and less prone to bugs when we change the logic
         // Notice the use of Sprite and Transform alongside Wall, overwriting the default values defined for the required components
-        pub fn new(location: WallLocation) -> (Wall, Sprite, Transform) {
+        pub fn new(
+            location: WallLocation,
+            arena: &Arena,
+            config: &BreakoutConfig,
+        ) -> (Wall, WallLocation, Shape, Sprite, Transform) {
             (
                 Wall,
-                Sprite::from_color(WALL_COLOR, Vec2::ONE),
+                location,
+                unit_square(),
+                Sprite::from_color(config.wall_color, Vec2::ONE),
                 Transform {
                     // We need to convert our Vec2 into a Vec3, by giving it a z-coordinate
                     // This is used to determine the order of our sprites
-                    translation: location.position().extend(0.0),
+                    translation: location.position(arena).extend(0.0),
                     // The z-scale of 2D objects must always be 1.0,
                     // or their ordering will be affected in surprising ways.
                     // See https://github.com/bevyengine/bevy/issues/4149
-                    scale: location.size().extend(1.0),
+                    scale: location.size(arena).extend(1.0),
                     ..default()
                 },
             )
         }
     }
-
-    #[derive(Component)]
-    pub struct ScoreboardUi;
 }
 
 pub mod bundles {
     use super::components::*;
     use super::constants::*;
-    use super::resources::*;
+    use super::resources::BreakoutConfig;
+    use super::states::GameState;
     use bevy::prelude::*;
+    use bevy_sandbox::collision::Shape;
+    use bevy_sandbox::velocity::{ManualMotion, Velocity};
 
     #[derive(Bundle)]
     pub struct PaddleBundle {
         pub sprite: Sprite,
         pub transform: Transform,
         pub paddle: Paddle,
+        pub width: PaddleWidth,
         pub bullet_cooldown: BulletCooldown,
+        pub shape: Shape,
         pub collider: Collider,
         pub velocity: Velocity,
     }
 
-    impl Default for PaddleBundle {
-        fn default() -> Self {
+    impl PaddleBundle {
+        pub fn new(config: &BreakoutConfig) -> Self {
             Self {
                 sprite: Sprite {
-                    color: PADDLE_COLOR,
+                    color: config.paddle_color,
                     ..default()
                 },
                 transform: Transform {
                     translation: Vec3::new(0.0, 0.0, 0.0),
-                    scale: PADDLE_SIZE.extend(1.0),
+                    scale: config.paddle_size.extend(1.0),
                     ..default()
                 },
                 paddle: Paddle,
+                width: PaddleWidth { a: 1.0 },
                 bullet_cooldown: BulletCooldown::default(),
+                shape: unit_square(),
                 collider: Collider,
                 velocity: Velocity(Vec2::ZERO),
             }
@@ -290,22 +590,70 @@ pub mod bundles {
         pub material: MeshMaterial2d<ColorMaterial>,
         pub transform: Transform,
         pub ball: Ball,
+        pub shape: Shape,
         pub velocity: Velocity,
+        pub speed: Speed,
+        pub manual_motion: ManualMotion,
+        pub state_scoped: StateScoped<GameState>,
     }
 
     impl BallBundle {
         pub fn new(
             meshes: &mut ResMut<Assets<Mesh>>,
             materials: &mut ResMut<Assets<ColorMaterial>>,
-            ball_speed: &ResMut<Speed>,
+            config: &BreakoutConfig,
+            speed: f32,
+        ) -> Self {
+            Self::at(
+                meshes,
+                materials,
+                config,
+                BALL_STARTING_POSITION,
+                INITIAL_BALL_DIRECTION.normalize(),
+                speed,
+            )
+        }
+
+        // A ball on top of the paddle, held there until it's served
+        pub fn served(
+            meshes: &mut ResMut<Assets<Mesh>>,
+            materials: &mut ResMut<Assets<ColorMaterial>>,
+            config: &BreakoutConfig,
+            paddle_position: Vec3,
+            speed: f32,
+        ) -> (Self, Served) {
+            let position = Vec3::new(
+                paddle_position.x,
+                paddle_position.y + config.serve_height(),
+                BALL_STARTING_POSITION.z,
+            );
+
+            (
+                Self::at(meshes, materials, config, position, Vec2::ZERO, speed),
+                Served::default(),
+            )
+        }
+
+        // A ball at `position` going in `direction`, standing still if it's zero
+        pub fn at(
+            meshes: &mut ResMut<Assets<Mesh>>,
+            materials: &mut ResMut<Assets<ColorMaterial>>,
+            config: &BreakoutConfig,
+            position: Vec3,
+            direction: Vec2,
+            speed: f32,
         ) -> Self {
             Self {
                 mesh: Mesh2d(meshes.add(Circle::default())),
-                material: MeshMaterial2d(materials.add(BALL_COLOR)),
-                transform: Transform::from_translation(BALL_STARTING_POSITION)
-                    .with_scale(Vec2::splat(BALL_DIAMETER).extend(1.0)),
+                material: MeshMaterial2d(materials.add(config.ball_color)),
+                transform: Transform::from_translation(position)
+                    .with_scale(Vec2::splat(config.ball_diameter).extend(1.0)),
                 ball: Ball,
-                velocity: Velocity(INITIAL_BALL_DIRECTION.normalize() * ball_speed.a),
+                shape: Shape::Circle(Circle::default()),
+                velocity: Velocity(direction * speed),
+                speed: Speed { a: speed },
+                manual_motion: ManualMotion,
+                state_scoped: StateScoped(GameState::Playing),
             }
         }
     }
@@ -316,22 +664,68 @@ pub mod bundles {
         pub material: MeshMaterial2d<ColorMaterial>,
         pub transform: Transform,
         pub bullet: Bullet,
+        pub shape: Shape,
         pub velocity: Velocity,
+        pub speed: Speed,
+        pub manual_motion: ManualMotion,
+        pub state_scoped: StateScoped<GameState>,
     }
 
     impl BulletBundle {
         pub fn new(
             meshes: &mut ResMut<Assets<Mesh>>,
             materials: &mut ResMut<Assets<ColorMaterial>>,
+            config: &BreakoutConfig,
             starting_position: Vec3,
+            speed: f32,
         ) -> Self {
             Self {
                 mesh: Mesh2d(meshes.add(Annulus::default())),
-                material: MeshMaterial2d(materials.add(BULLET_COLOR)),
+                material: MeshMaterial2d(materials.add(config.bullet_color)),
                 transform: Transform::from_translation(starting_position)
-                    .with_scale(Vec2::splat(BULLET_DIAMETER).extend(1.0)),
+                    .with_scale(Vec2::splat(config.bullet_diameter).extend(1.0)),
                 bullet: Bullet,
-                velocity: Velocity(INITIAL_BULLET_DIRECTION * BULLET_SPEED),
+                // The ring is drawn bigger than what it hits with
+                shape: Shape::Circle(Circle::default()),
+                velocity: Velocity(INITIAL_BULLET_DIRECTION * speed),
+                speed: Speed { a: speed },
+                manual_motion: ManualMotion,
+                state_scoped: StateScoped(GameState::Playing),
+            }
+        }
+    }
+
+    #[derive(Bundle)]
+    pub struct CapsuleBundle {
+        pub sprite: Sprite,
+        pub transform: Transform,
+        pub capsule: Capsule,
+        pub shape: Shape,
+        pub velocity: Velocity,
+        pub state_scoped: StateScoped<GameState>,
+    }
+
+    impl CapsuleBundle {
+        pub fn new(
+            config: &BreakoutConfig,
+            position: Vec2,
+            power_up: PowerUp,
+            fall_speed: f32,
+        ) -> Self {
+            Self {
+                sprite: Sprite {
+                    color: power_up.color(config),
+                    ..default()
+                },
+                transform: Transform {
+                    translation: position.extend(0.5),
+                    scale: config.capsule_size.extend(1.0),
+                    ..default()
+                },
+                capsule: Capsule { power_up },
+                shape: unit_square(),
+                velocity: Velocity(Vec2::NEG_Y * fall_speed),
+                state_scoped: StateScoped(GameState::Playing),
             }
         }
     }
@@ -341,35 +735,327 @@ pub mod bundles {
         pub sprite: Sprite,
         pub transform: Transform,
         pub brick: Brick,
+        pub shape: Shape,
         pub collider: Collider,
+        pub state_scoped: StateScoped<GameState>,
     }
 
     impl BrickBundle {
-        pub fn new(brick_position: Vec2, r#type: BrickType) -> Self {
+        pub fn new(
+            config: &BreakoutConfig,
+            brick_position: Vec2,
+            r#type: BrickType,
+            hit_points: u32,
+        ) -> Self {
+            let brick = Brick { r#type, hit_points };
             Self {
                 sprite: Sprite {
-                    color: BrickType::color(&r#type),
+                    color: brick.color(config),
                     ..default()
                 },
                 transform: Transform {
                     translation: brick_position.extend(0.0),
-                    scale: Vec3::new(BRICK_SIZE.x, BRICK_SIZE.y, 1.0),
+                    scale: config.brick_size.extend(1.0),
                     ..default()
                 },
-                brick: Brick { r#type: r#type },
+                brick,
+                shape: unit_square(),
                 collider: Collider,
+                state_scoped: StateScoped(GameState::Playing),
             }
         }
     }
 }
 
 pub mod resources {
+    use super::components::{BrickType, PowerUp};
     use super::constants::*;
     use bevy::prelude::*;
+    use bevy_sandbox::config::{Config, ConfigError};
+    use bevy_sandbox::screen::TextColors;
+    use serde::Deserialize;
+
+    // Tuning of the paddle, balls, bricks and power-ups, from `assets/config/breakout.ron`
+    #[derive(Asset, Resource, TypePath, Clone, PartialEq, Debug, Deserialize)]
+    #[serde(default, deny_unknown_fields)]
+    pub struct BreakoutConfig {
+        // Seed of the brick layout, instead of a fresh one. `--seed` wins over it.
+        pub seed: Option<u64>,
+        pub paddle_size: Vec2,
+        pub paddle_speed: f32,
+        pub paddle_padding: f32,
+        pub paddle_bounce_max_angle: f32,
+        pub starting_lives: u32,
+        pub ball_diameter: f32,
+        pub ball_speed: f32,
+        pub ball_speed_multiplier: f32,
+        pub ball_max_speed: f32,
+        pub bullet_diameter: f32,
+        pub bullet_speed: f32,
+        pub bullet_cooldown: f32,
+        pub brick_size: Vec2,
+        pub brick_type_normal_weight: f32,
+        pub brick_type_speed_weight: f32,
+        pub brick_type_extra_ball_weight: f32,
+        pub brick_type_tough_weight: f32,
+        pub brick_type_indestructible_weight: f32,
+        pub brick_type_explosive_weight: f32,
+        pub brick_type_paddle_grow_weight: f32,
+        pub brick_type_paddle_shrink_weight: f32,
+        pub tough_brick_hit_points: u32,
+        pub explosion_radius: f32,
+        pub paddle_resize_multiplier: f32,
+        pub paddle_min_width: f32,
+        pub paddle_max_width: f32,
+        pub capsule_size: Vec2,
+        pub capsule_drop_chance: f32,
+        pub capsule_fall_speed: f32,
+        pub power_up_wide_paddle_weight: f32,
+        pub power_up_sticky_paddle_weight: f32,
+        pub power_up_slow_ball_weight: f32,
+        pub power_up_laser_paddle_weight: f32,
+        pub power_up_multi_ball_weight: f32,
+        pub power_up_duration: f32,
+        pub wide_paddle_multiplier: f32,
+        pub slow_ball_multiplier: f32,
+        pub multi_ball_spread: f32,
+        pub background_color: Color,
+        pub paddle_color: Color,
+        pub ball_color: Color,
+        pub bullet_color: Color,
+        pub wall_color: Color,
+        pub brick_normal_color: Color,
+        pub brick_speed_color: Color,
+        pub brick_extra_ball_color: Color,
+        pub brick_tough_color: Color,
+        pub brick_indestructible_color: Color,
+        pub brick_explosive_color: Color,
+        pub brick_paddle_grow_color: Color,
+        pub brick_paddle_shrink_color: Color,
+        // Each hit point over one makes a brick this much darker
+        pub brick_damage_darkening: f32,
+        pub wide_paddle_color: Color,
+        pub sticky_paddle_color: Color,
+        pub slow_ball_color: Color,
+        pub laser_paddle_color: Color,
+        pub multi_ball_color: Color,
+        pub text_color: Color,
+        pub score_color: Color,
+        pub title_color: Color,
+        pub screen_overlay_color: Color,
+    }
+
+    impl Default for BreakoutConfig {
+        fn default() -> Self {
+            BreakoutConfig {
+                seed: None,
+                paddle_size: PADDLE_SIZE,
+                paddle_speed: PADDLE_SPEED,
+                paddle_padding: PADDLE_PADDING,
+                paddle_bounce_max_angle: PADDLE_BOUNCE_MAX_ANGLE,
+                starting_lives: STARTING_LIVES,
+                ball_diameter: BALL_DIAMETER,
+                ball_speed: BALL_SPEED,
+                ball_speed_multiplier: BALL_SPEED_MULTIPLIER,
+                ball_max_speed: BALL_MAX_SPEED,
+                bullet_diameter: BULLET_DIAMETER,
+                bullet_speed: BULLET_SPEED,
+                bullet_cooldown: BULLET_COOLDOWN,
+                brick_size: BRICK_SIZE,
+                brick_type_normal_weight: BRICK_TYPE_NORMAL_WEIGHT,
+                brick_type_speed_weight: BRICK_TYPE_SPEED_WEIGHT,
+                brick_type_extra_ball_weight: BRICK_TYPE_EXTRA_BALL_WEIGHT,
+                brick_type_tough_weight: BRICK_TYPE_TOUGH_WEIGHT,
+                brick_type_indestructible_weight: BRICK_TYPE_INDESTRUCTIBLE_WEIGHT,
+                brick_type_explosive_weight: BRICK_TYPE_EXPLOSIVE_WEIGHT,
+                brick_type_paddle_grow_weight: BRICK_TYPE_PADDLE_GROW_WEIGHT,
+                brick_type_paddle_shrink_weight: BRICK_TYPE_PADDLE_SHRINK_WEIGHT,
+                tough_brick_hit_points: TOUGH_BRICK_HIT_POINTS,
+                explosion_radius: EXPLOSION_RADIUS,
+                paddle_resize_multiplier: PADDLE_RESIZE_MULTIPLIER,
+                paddle_min_width: PADDLE_MIN_WIDTH,
+                paddle_max_width: PADDLE_MAX_WIDTH,
+                capsule_size: CAPSULE_SIZE,
+                capsule_drop_chance: CAPSULE_DROP_CHANCE,
+                capsule_fall_speed: CAPSULE_FALL_SPEED,
+                power_up_wide_paddle_weight: POWER_UP_WIDE_PADDLE_WEIGHT,
+                power_up_sticky_paddle_weight: POWER_UP_STICKY_PADDLE_WEIGHT,
+                power_up_slow_ball_weight: POWER_UP_SLOW_BALL_WEIGHT,
+                power_up_laser_paddle_weight: POWER_UP_LASER_PADDLE_WEIGHT,
+                power_up_multi_ball_weight: POWER_UP_MULTI_BALL_WEIGHT,
+                power_up_duration: POWER_UP_DURATION,
+                wide_paddle_multiplier: WIDE_PADDLE_MULTIPLIER,
+                slow_ball_multiplier: SLOW_BALL_MULTIPLIER,
+                multi_ball_spread: MULTI_BALL_SPREAD,
+                background_color: BACKGROUND_COLOR,
+                paddle_color: PADDLE_COLOR,
+                ball_color: BALL_COLOR,
+                bullet_color: BULLET_COLOR,
+                wall_color: WALL_COLOR,
+                brick_normal_color: BRICK_NORMAL_COLOR,
+                brick_speed_color: BRICK_SPEED_COLOR,
+                brick_extra_ball_color: BRICK_EXTRA_BALL_COLOR,
+                brick_tough_color: BRICK_TOUGH_COLOR,
+                brick_indestructible_color: BRICK_INDESTRUCTIBLE_COLOR,
+                brick_explosive_color: BRICK_EXPLOSIVE_COLOR,
+                brick_paddle_grow_color: BRICK_PADDLE_GROW_COLOR,
+                brick_paddle_shrink_color: BRICK_PADDLE_SHRINK_COLOR,
+                brick_damage_darkening: BRICK_DAMAGE_DARKENING,
+                wide_paddle_color: WIDE_PADDLE_COLOR,
+                sticky_paddle_color: STICKY_PADDLE_COLOR,
+                slow_ball_color: SLOW_BALL_COLOR,
+                laser_paddle_color: LASER_PADDLE_COLOR,
+                multi_ball_color: MULTI_BALL_COLOR,
+                text_color: TEXT_COLOR,
+                score_color: SCORE_COLOR,
+                title_color: TITLE_COLOR,
+                screen_overlay_color: SCREEN_OVERLAY_COLOR,
+            }
+        }
+    }
 
-    #[derive(Resource, Deref)]
-    pub struct CollisionSound {
-        pub a: Handle<AudioSource>,
+    impl BreakoutConfig {
+        // How far above the middle of the paddle served balls wait, resting on top of it
+        pub fn serve_height(&self) -> f32 {
+            self.paddle_size.y / 2.0 + self.ball_diameter / 2.0 + 1.0
+        }
+
+        pub fn text_colors(&self) -> TextColors {
+            TextColors {
+                overlay: self.screen_overlay_color,
+                title: self.title_color,
+                text: self.text_color,
+                value: self.score_color,
+            }
+        }
+    }
+
+    impl Config for BreakoutConfig {
+        fn validate(&self) -> Result<(), ConfigError> {
+            let mut error = ConfigError::default();
+            error.positive_size("paddle_size", self.paddle_size);
+            error.positive("paddle_speed", self.paddle_speed);
+            error.non_negative("paddle_padding", self.paddle_padding);
+            // Balls always leave the paddle upwards, so they can't end up going sideways forever
+            error.check(
+                (0.0..90.0).contains(&self.paddle_bounce_max_angle),
+                "`paddle_bounce_max_angle` must be at least 0 and less than 90 degrees",
+            );
+            error.check(
+                self.starting_lives > 0,
+                "`starting_lives` must be at least 1",
+            );
+            error.positive("ball_diameter", self.ball_diameter);
+            error.positive("ball_speed", self.ball_speed);
+            error.positive("ball_speed_multiplier", self.ball_speed_multiplier);
+            error.check(
+                self.ball_max_speed >= self.ball_speed,
+                "`ball_max_speed` must not be under `ball_speed`",
+            );
+            error.positive("bullet_diameter", self.bullet_diameter);
+            error.positive("bullet_speed", self.bullet_speed);
+            error.non_negative("bullet_cooldown", self.bullet_cooldown);
+            error.positive_size("brick_size", self.brick_size);
+            let weight_names = [
+                "brick_type_normal_weight",
+                "brick_type_speed_weight",
+                "brick_type_extra_ball_weight",
+                "brick_type_tough_weight",
+                "brick_type_indestructible_weight",
+                "brick_type_explosive_weight",
+                "brick_type_paddle_grow_weight",
+                "brick_type_paddle_shrink_weight",
+            ];
+            let weights = BrickType::weights(self);
+            for (name, weight) in weight_names.iter().zip(&weights) {
+                error.non_negative(name, *weight);
+            }
+            error.check(
+                weights.iter().sum::<f32>() > 0.0,
+                "at least one brick type weight must be positive",
+            );
+            error.check(
+                self.tough_brick_hit_points > 0,
+                "`tough_brick_hit_points` must be at least 1",
+            );
+            error.non_negative("explosion_radius", self.explosion_radius);
+            error.check(
+                self.paddle_resize_multiplier >= 1.0 && self.paddle_resize_multiplier.is_finite(),
+                format!(
+                    "`paddle_resize_multiplier` must be at least 1, got {}",
+                    self.paddle_resize_multiplier
+                ),
+            );
+            error.positive("paddle_min_width", self.paddle_min_width);
+            error.check(
+                self.paddle_min_width <= self.paddle_max_width,
+                "`paddle_min_width` must not be over `paddle_max_width`",
+            );
+            error.positive_size("capsule_size", self.capsule_size);
+            error.check(
+                (0.0..=1.0).contains(&self.capsule_drop_chance),
+                format!(
+                    "`capsule_drop_chance` must be between 0 and 1, got {}",
+                    self.capsule_drop_chance
+                ),
+            );
+            error.positive("capsule_fall_speed", self.capsule_fall_speed);
+            error.non_negative("brick_damage_darkening", self.brick_damage_darkening);
+            let power_up_weight_names = [
+                "power_up_wide_paddle_weight",
+                "power_up_sticky_paddle_weight",
+                "power_up_slow_ball_weight",
+                "power_up_laser_paddle_weight",
+                "power_up_multi_ball_weight",
+            ];
+            let power_up_weights = PowerUp::weights(self);
+            for (name, weight) in power_up_weight_names.iter().zip(&power_up_weights) {
+                error.non_negative(name, *weight);
+            }
+            error.check(
+                power_up_weights.iter().sum::<f32>() > 0.0,
+                "at least one power-up weight must be positive",
+            );
+            error.non_negative("power_up_duration", self.power_up_duration);
+            error.positive("wide_paddle_multiplier", self.wide_paddle_multiplier);
+            error.positive("slow_ball_multiplier", self.slow_ball_multiplier);
+            error.non_negative("multi_ball_spread", self.multi_ball_spread);
+            error.into_result()
+        }
+    }
+
+    // Seconds left of each timed power-up, in the order of `PowerUp::ALL`
+    #[derive(Resource, Default)]
+    pub struct PowerUps {
+        pub remaining: [f32; PowerUp::ALL.len()],
+    }
+
+    impl PowerUps {
+        pub fn is_active(&self, power_up: PowerUp) -> bool {
+            self.remaining[power_up as usize] > 0.0
+        }
+
+        pub fn start(&mut self, power_up: PowerUp, duration: f32) {
+            self.remaining[power_up as usize] = duration;
+        }
+
+        // What the HUD shows, like `Wide 7s, Laser 2s`
+        pub fn describe(&self) -> String {
+            let active = PowerUp::ALL
+                .iter()
+                .filter(|power_up| self.is_active(**power_up))
+                .map(|power_up| {
+                    let seconds = self.remaining[*power_up as usize].ceil();
+                    format!("{} {seconds}s", power_up.name())
+                })
+                .collect::<Vec<_>>();
+
+            if active.is_empty() {
+                "-".to_string()
+            } else {
+                active.join(", ")
+            }
+        }
     }
 
     // This resource tracks the game's score
@@ -378,42 +1064,347 @@ pub mod resources {
         pub a: usize,
     }
 
-    // This resource tracks the game's score
+    // Balls the player can still lose before the game is over
     #[derive(Resource, Deref, DerefMut)]
-    pub struct Speed {
-        pub a: f32,
+    pub struct Lives {
+        pub a: u32,
+    }
+}
+
+pub mod levels {
+    use super::components::BrickType;
+    use super::constants::*;
+    use bevy::asset::io::Reader;
+    use bevy::asset::{AssetLoader, LoadContext};
+    use bevy::prelude::*;
+    use bevy_sandbox::config::ConfigError;
+    use serde::Deserialize;
+
+    // Where the walls of the arena are, along their middle lines
+    #[derive(Resource, Clone, Copy, PartialEq, Debug, Deserialize)]
+    #[serde(default, deny_unknown_fields)]
+    pub struct Arena {
+        pub left: f32,
+        pub right: f32,
+        pub bottom: f32,
+        pub top: f32,
     }
 
-    impl Default for Speed {
+    impl Default for Arena {
         fn default() -> Self {
-            Speed { a: BALL_SPEED }
+            Arena {
+                left: LEFT_WALL,
+                right: RIGHT_WALL,
+                bottom: BOTTOM_WALL,
+                top: TOP_WALL,
+            }
         }
     }
-}
 
-pub mod events {
-    use bevy::prelude::*;
-    #[derive(Event, Default)]
-    pub struct BallCollisionEvent;
+    impl Arena {
+        pub fn width(&self) -> f32 {
+            self.right - self.left
+        }
+
+        pub fn height(&self) -> f32 {
+            self.top - self.bottom
+        }
+
+        pub fn center(&self) -> Vec2 {
+            Vec2::new(self.left + self.right, self.bottom + self.top) / 2.0
+        }
+
+        pub fn paddle_y(&self) -> f32 {
+            self.bottom + GAP_BETWEEN_PADDLE_AND_FLOOR
+        }
+    }
+
+    // A cell of the grid the bricks of a level are laid out in, counted from the top left
+    #[derive(Component, Clone, Copy, PartialEq, Debug)]
+    pub struct GridCell {
+        pub row: usize,
+        pub column: usize,
+    }
+
+    // The rows and columns of bricks of a level, centered under the ceiling of its arena.
+    // The bricks are laid out as they spawn, as big as the config makes them.
+    #[derive(Resource, Clone, Copy, PartialEq, Debug, Default)]
+    pub struct Grid {
+        pub rows: usize,
+        pub columns: usize,
+    }
+
+    impl Grid {
+        // Width and height of all the bricks, with the gaps between them
+        fn extent(&self, brick_size: Vec2) -> Vec2 {
+            Vec2::new(self.columns as f32, self.rows as f32) * (brick_size + GAP_BETWEEN_BRICKS)
+                - GAP_BETWEEN_BRICKS
+        }
+
+        // Checks that bricks of `brick_size` fit between the sides, and above the paddle
+        pub fn check_fit(&self, arena: &Arena, brick_size: Vec2) -> Result<(), ConfigError> {
+            let mut error = ConfigError::default();
+            let extent = self.extent(brick_size);
+            let top_edge = arena.top - GAP_BETWEEN_BRICKS_AND_CEILING;
+            error.check(
+                extent.x <= arena.width() - 2.0 * GAP_BETWEEN_BRICKS_AND_SIDES,
+                format!(
+                    "{} columns of bricks are too wide for the arena",
+                    self.columns
+                ),
+            );
+            error.check(
+                top_edge - extent.y >= arena.paddle_y() + GAP_BETWEEN_PADDLE_AND_BRICKS,
+                format!("{} rows of bricks come too close to the paddle", self.rows),
+            );
+            error.into_result()
+        }
+
+        // Where the middle of the brick in `cell` goes
+        pub fn position(&self, arena: &Arena, cell: GridCell, brick_size: Vec2) -> Vec2 {
+            // In Bevy, the `translation` of an entity describes the center point,
+            // not its bottom-left corner
+            let offset_x = arena.center().x - self.extent(brick_size).x / 2.0 + brick_size.x / 2.0;
+            let offset_y = arena.top - GAP_BETWEEN_BRICKS_AND_CEILING - brick_size.y / 2.0;
+            Vec2::new(
+                offset_x + cell.column as f32 * (brick_size.x + GAP_BETWEEN_BRICKS),
+                offset_y - cell.row as f32 * (brick_size.y + GAP_BETWEEN_BRICKS),
+            )
+        }
+    }
+
+    // A brick of a level. Its type is picked with the config weights when it's `None`,
+    // and it takes as many hits as bricks of its type do when its hit points are.
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    pub struct LevelBrick {
+        pub cell: GridCell,
+        pub r#type: Option<BrickType>,
+        pub hit_points: Option<u32>,
+    }
+
+    #[derive(Asset, TypePath, Clone, PartialEq, Debug)]
+    pub struct Level {
+        pub name: String,
+        pub arena: Arena,
+        pub grid: Grid,
+        pub bricks: Vec<LevelBrick>,
+    }
+
+    // A level as written in its file. The bricks are rows of space separated cells,
+    // top row first, all as long as each other:
+    // `.` is no brick, a letter of `BrickType::from_letter` is a brick of that type,
+    // and `?` is a brick of a random type. A number after the letter is how many hits
+    // the brick takes, as many as its type takes if there's none, like `S3`.
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct LevelFile {
+        name: String,
+        #[serde(default)]
+        arena: Arena,
+        bricks: Vec<String>,
+    }
+
+    type Cell = Option<(Option<BrickType>, Option<u32>)>;
+
+    fn parse_cell(cell: &str) -> Result<Cell, String> {
+        if cell == "." {
+            return Ok(None);
+        }
+
+        let mut chars = cell.chars();
+        let letter = chars.next().unwrap_or_default();
+        let r#type = match letter {
+            '?' => None,
+            _ => Some(
+                BrickType::from_letter(letter)
+                    .ok_or_else(|| format!("unknown brick `{letter}` in `{cell}`"))?,
+            ),
+        };
+
+        let hit_points = match chars.as_str() {
+            "" => None,
+            digits => Some(
+                digits
+                    .parse()
+                    .ok()
+                    .filter(|hit_points| *hit_points > 0)
+                    .ok_or_else(|| {
+                        format!("bad hit points in `{cell}`, they must be at least 1")
+                    })?,
+            ),
+        };
+
+        Ok(Some((r#type, hit_points)))
+    }
+
+    // Puts the rows in a grid, and checks they fit above the paddle. Levels are made
+    // for the default brick size, the one of the config is checked as they're played.
+    fn lay_out(arena: &Arena, rows: &[Vec<Cell>]) -> Result<(Grid, Vec<LevelBrick>), ConfigError> {
+        let mut error = ConfigError::default();
+        let grid = Grid {
+            rows: rows.len(),
+            columns: rows.first().map_or(0, Vec::len),
+        };
+
+        error.check(
+            arena.width() > 0.0 && arena.height() > 0.0,
+            "the arena must have a positive width and height",
+        );
+        error.check(grid.columns > 0, "a level needs at least one brick");
+        error.check(
+            rows.iter().all(|row| row.len() == grid.columns),
+            "the rows of bricks must all be as long as each other",
+        );
+        if let Err(fit) = grid.check_fit(arena, BRICK_SIZE) {
+            error.problems.extend(fit.problems);
+        }
+        error.into_result()?;
+
+        let mut bricks = Vec::new();
+        for (row, cells) in rows.iter().enumerate() {
+            for (column, cell) in cells.iter().enumerate() {
+                if let Some((r#type, hit_points)) = *cell {
+                    bricks.push(LevelBrick {
+                        cell: GridCell { row, column },
+                        r#type,
+                        hit_points,
+                    });
+                }
+            }
+        }
+        Ok((grid, bricks))
+    }
+
+    // Parses and checks a level, so a broken file fails to load instead of reaching the game
+    pub fn parse_level(bytes: &[u8]) -> Result<Level, ConfigError> {
+        let file: LevelFile = ron::de::from_bytes(bytes).map_err(|error| error.to_string())?;
+
+        let mut error = ConfigError::default();
+        let mut rows = Vec::with_capacity(file.bricks.len());
+        for row in &file.bricks {
+            let mut cells = Vec::new();
+            for cell in row.split_whitespace() {
+                match parse_cell(cell) {
+                    Ok(cell) => cells.push(cell),
+                    Err(problem) => error.problems.push(problem),
+                }
+            }
+            rows.push(cells);
+        }
+        error.into_result()?;
+
+        let (grid, bricks) = lay_out(&file.arena, &rows)?;
+        Ok(Level {
+            name: file.name,
+            arena: file.arena,
+            grid,
+            bricks,
+        })
+    }
+
+    impl Level {
+        // The wall of random bricks filling the default arena, for when no level loads.
+        // There's at least one brick, even one too big to fit.
+        pub fn generated(brick_size: Vec2) -> Self {
+            let arena = Arena::default();
+            let width = arena.width() - 2.0 * GAP_BETWEEN_BRICKS_AND_SIDES + GAP_BETWEEN_BRICKS;
+            let height = arena.top
+                - GAP_BETWEEN_BRICKS_AND_CEILING
+                - arena.paddle_y()
+                - GAP_BETWEEN_PADDLE_AND_BRICKS
+                + GAP_BETWEEN_BRICKS;
+
+            // Given the space available, compute how many rows and columns of bricks we can fit
+            let grid = Grid {
+                columns: ((width / (brick_size.x + GAP_BETWEEN_BRICKS)).floor() as usize).max(1),
+                rows: ((height / (brick_size.y + GAP_BETWEEN_BRICKS)).floor() as usize).max(1),
+            };
+            let bricks = (0..grid.rows)
+                .flat_map(|row| (0..grid.columns).map(move |column| GridCell { row, column }))
+                .map(|cell| LevelBrick {
+                    cell,
+                    r#type: None,
+                    hit_points: None,
+                })
+                .collect();
+
+            Level {
+                name: "Generated".to_string(),
+                arena,
+                grid,
+                bricks,
+            }
+        }
+    }
+
+    #[derive(Default)]
+    pub struct LevelLoader;
+
+    impl AssetLoader for LevelLoader {
+        type Asset = Level;
+        type Settings = ();
+        type Error = ConfigError;
+
+        async fn load(
+            &self,
+            reader: &mut dyn Reader,
+            _settings: &(),
+            _load_context: &mut LoadContext<'_>,
+        ) -> Result<Level, ConfigError> {
+            let mut bytes = Vec::new();
+            reader
+                .read_to_end(&mut bytes)
+                .await
+                .map_err(|error| error.to_string())?;
+            parse_level(&bytes)
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &["level.ron"]
+        }
+    }
+
+    // The levels of a game, played in order, and the one being played.
+    // Levels that failed to load are left out once loading is over.
+    #[derive(Resource, Default)]
+    pub struct Levels {
+        pub a: Vec<Handle<Level>>,
+        pub current: usize,
+    }
+
+    impl Levels {
+        pub fn is_last(&self) -> bool {
+            self.current + 1 >= self.a.len()
+        }
+    }
 }
 
 pub mod systems {
     use super::bundles::*;
     use super::components::*;
     use super::constants::*;
-    use super::events::*;
+    use super::levels::*;
     use super::resources::*;
-    use bevy::math::bounding::{Aabb2d, BoundingCircle, BoundingVolume, IntersectsVolume};
+    use super::states::*;
+    use bevy::asset::LoadState;
+    use bevy::ecs::entity::EntityHashSet;
+    use bevy::math::bounding::BoundingVolume as _;
     use bevy::prelude::*;
+    use bevy_sandbox::collision::*;
+    use bevy_sandbox::collision_sound::*;
+    use bevy_sandbox::input::*;
+    use bevy_sandbox::rng::GameRng;
+    use bevy_sandbox::scoreboard::*;
+    use bevy_sandbox::screen::{ScreenStyle, restyle_text};
+    use bevy_sandbox::velocity::Velocity;
 
     // Add the game's entities to our world
     pub fn setup(
         mut window: Single<&mut Window>,
         mut commands: Commands,
-        mut meshes: ResMut<Assets<Mesh>>,
-        mut materials: ResMut<Assets<ColorMaterial>>,
-        asset_server: Res<AssetServer>,
-        ball_speed: ResMut<Speed>,
+        config: Res<BreakoutConfig>,
+        scoreboard_style: Res<ScoreboardStyle>,
+        arena: Res<Arena>,
     ) {
         // Set up the window
         window.set_maximized(true);
@@ -421,332 +1412,826 @@ pub mod systems {
         // Camera
         commands.spawn(Camera2d);
 
-        // Sound
-        let ball_collision_sound = asset_server.load("sounds/breakout_collision.ogg");
-        commands.insert_resource(CollisionSound {
-            a: ball_collision_sound,
-        });
-
         // Paddle
-        let paddle_y = BOTTOM_WALL + GAP_BETWEEN_PADDLE_AND_FLOOR;
-
-        let mut paddle_bundle = PaddleBundle::default();
-        paddle_bundle.transform.translation.y = paddle_y;
+        let mut paddle_bundle = PaddleBundle::new(&config);
+        paddle_bundle.transform.translation.y = arena.paddle_y();
         commands.spawn(paddle_bundle);
 
-        // Ball
-        if SPAWN_BALLS {
-            // If we want to spawn a ball, we use the `BallBundle::new` method
-            commands.spawn(BallBundle::new(&mut meshes, &mut materials, &ball_speed));
-        }
-
         // Scoreboard
         commands.spawn((
-            Text::new("Score: "),
-            TextFont {
-                font_size: SCOREBOARD_FONT_SIZE,
-                ..default()
-            },
-            TextColor(TEXT_COLOR),
-            ScoreboardUi,
-            Node {
-                position_type: PositionType::Absolute,
-                top: SCOREBOARD_TEXT_PADDING,
-                left: SCOREBOARD_TEXT_PADDING,
-                ..default()
-            },
-            children![(
-                TextSpan::default(),
-                TextFont {
-                    font_size: SCOREBOARD_FONT_SIZE,
-                    ..default()
-                },
-                TextColor(SCORE_COLOR),
-            )],
+            scoreboard_style.root(),
+            children![
+                scoreboard_style.line("Score: ", "0"),
+                scoreboard_style.line("Lives: ", STARTING_LIVES.to_string()),
+                scoreboard_style.line("Level: ", "1"),
+                scoreboard_style.line("Power-ups: ", "-"),
+            ],
         ));
 
         // Walls
-        commands.spawn(Wall::new(WallLocation::Left));
-        commands.spawn(Wall::new(WallLocation::Right));
-        commands.spawn(Wall::new(WallLocation::Bottom));
-        commands.spawn(Wall::new(WallLocation::Top));
-
-        // Bricks
-        let total_width_of_bricks = (RIGHT_WALL - LEFT_WALL) - 2. * GAP_BETWEEN_BRICKS_AND_SIDES;
-        let bottom_edge_of_bricks = paddle_y + GAP_BETWEEN_PADDLE_AND_BRICKS;
-        let total_height_of_bricks =
-            TOP_WALL - bottom_edge_of_bricks - GAP_BETWEEN_BRICKS_AND_CEILING;
-
-        assert!(total_width_of_bricks > 0.0);
-        assert!(total_height_of_bricks > 0.0);
-
-        // Given the space available, compute how many rows and columns of bricks we can fit
-        let n_columns =
-            (total_width_of_bricks / (BRICK_SIZE.x + GAP_BETWEEN_BRICKS)).floor() as usize;
-        let n_rows =
-            (total_height_of_bricks / (BRICK_SIZE.y + GAP_BETWEEN_BRICKS)).floor() as usize;
-        let n_vertical_gaps = n_columns - 1;
-
-        // Because we need to round the number of columns,
-        // the space on the top and sides of the bricks only captures a lower bound, not an exact value
-        let center_of_bricks = (LEFT_WALL + RIGHT_WALL) / 2.0;
-        let left_edge_of_bricks = center_of_bricks
-        // Space taken up by the bricks
-        - (n_columns as f32 / 2.0 * BRICK_SIZE.x)
-        // Space taken up by the gaps
-        - n_vertical_gaps as f32 / 2.0 * GAP_BETWEEN_BRICKS;
-
-        // In Bevy, the `translation` of an entity describes the center point,
-        // not its bottom-left corner
-        let offset_x = left_edge_of_bricks + BRICK_SIZE.x / 2.;
-        let offset_y = bottom_edge_of_bricks + BRICK_SIZE.y / 2.;
-
-        // let mut rng_color = rand::thread_rng();
-        for row in 0..n_rows {
-            for column in 0..n_columns {
-                let brick_position = Vec2::new(
-                    offset_x + column as f32 * (BRICK_SIZE.x + GAP_BETWEEN_BRICKS),
-                    offset_y + row as f32 * (BRICK_SIZE.y + GAP_BETWEEN_BRICKS),
-                );
-
-                // brick
-                commands.spawn(BrickBundle::new(brick_position, BrickType::random()));
-            }
-        }
+        commands.spawn(Wall::new(WallLocation::Left, &arena, &config));
+        commands.spawn(Wall::new(WallLocation::Right, &arena, &config));
+        commands.spawn((Wall::new(WallLocation::Bottom, &arena, &config), KillZone));
+        commands.spawn(Wall::new(WallLocation::Top, &arena, &config));
     }
 
-    pub fn move_paddle(
-        keyboard_input: Res<ButtonInput<KeyCode>>,
-        mut paddle_transform: Single<&mut Transform, With<Paddle>>,
-        time: Res<Time>,
-    ) {
-        let mut direction = 0.0;
+    pub fn load_levels(mut commands: Commands, asset_server: Res<AssetServer>) {
+        commands.insert_resource(Levels {
+            a: LEVEL_PATHS.map(|path| asset_server.load(path)).into(),
+            current: 0,
+        });
+    }
 
-        if keyboard_input.pressed(KeyCode::ArrowLeft) {
-            direction -= 1.0;
-        }
+    // Run condition for leaving `Loading`, once every level has loaded or failed to
+    pub fn levels_loaded(levels: Res<Levels>, asset_server: Res<AssetServer>) -> bool {
+        levels.a.iter().all(|level| {
+            matches!(
+                asset_server.load_state(level),
+                LoadState::Loaded | LoadState::Failed(_)
+            )
+        })
+    }
 
-        if keyboard_input.pressed(KeyCode::ArrowRight) {
-            direction += 1.0;
+    // The game waits for the config, the seed of the brick layout and the tuning come from it,
+    // and for the levels. The asset server has already logged why the others failed.
+    pub fn finish_loading(
+        mut next_state: ResMut<NextState<GameState>>,
+        mut levels: ResMut<Levels>,
+        level_assets: Res<Assets<Level>>,
+    ) {
+        levels.a.retain(|level| level_assets.contains(level));
+        if levels.a.is_empty() {
+            warn!("No level loaded, playing a generated one");
         }
-
-        // Calculate the new horizontal paddle position based on player input
-        let new_paddle_position =
-            paddle_transform.translation.x + direction * PADDLE_SPEED * time.delta_secs();
-
-        // Update the paddle position,
-        // making sure it doesn't cause the paddle to leave the arena
-        let left_bound = LEFT_WALL + WALL_THICKNESS / 2.0 + PADDLE_SIZE.x / 2.0 + PADDLE_PADDING;
-        let right_bound = RIGHT_WALL - WALL_THICKNESS / 2.0 - PADDLE_SIZE.x / 2.0 - PADDLE_PADDING;
-
-        paddle_transform.translation.x = new_paddle_position.clamp(left_bound, right_bound);
+        next_state.set(GameState::Playing);
     }
 
-    pub fn apply_velocity(
-        mut query: Query<(&mut Transform, &Velocity)>,
-        ball_speed: Res<Speed>,
-        time: Res<Time>,
+    // A new game, from the first level
+    pub fn start_game(
+        config: Res<BreakoutConfig>,
+        mut rng: ResMut<GameRng>,
+        mut score: ResMut<Score>,
+        mut lives: ResMut<Lives>,
+        mut levels: ResMut<Levels>,
     ) {
-        for (mut transform, velocity) in &mut query {
-            let velocity = velocity.normalize_or_zero() * ball_speed.a;
-            transform.translation.x += velocity.x * time.delta_secs();
-            transform.translation.y += velocity.y * time.delta_secs();
-        }
+        rng.start_run(config.seed);
+        **score = 0;
+        **lives = config.starting_lives;
+        levels.current = 0;
     }
 
-    pub fn spawn_bullets(
+    // The bricks and the arena of the current level
+    pub fn spawn_level(
+        mut commands: Commands,
+        config: Res<BreakoutConfig>,
+        mut rng: ResMut<GameRng>,
+        levels: Res<Levels>,
+        level_assets: Res<Assets<Level>>,
+        mut arena: ResMut<Arena>,
+        mut grid: ResMut<Grid>,
+    ) {
+        let generated;
+        let level = match levels.a.get(levels.current) {
+            Some(handle) => level_assets.get(handle).expect("levels stay loaded"),
+            None => {
+                generated = Level::generated(config.brick_size);
+                &generated
+            }
+        };
+        info!("Level {}: {}", levels.current + 1, level.name);
+        if let Err(error) = level.grid.check_fit(&level.arena, config.brick_size) {
+            warn!("The bricks of {} don't fit the arena: {error}", level.name);
+        }
+
+        arena.set_if_neq(level.arena);
+        grid.set_if_neq(level.grid);
+        for brick in &level.bricks {
+            let r#type = brick
+                .r#type
+                .unwrap_or_else(|| BrickType::random(&config, &mut rng));
+            let hit_points = brick
+                .hit_points
+                .unwrap_or_else(|| r#type.hit_points(&config));
+            let position = level
+                .grid
+                .position(&level.arena, brick.cell, config.brick_size);
+            commands.spawn((
+                BrickBundle::new(&config, position, r#type, hit_points),
+                brick.cell,
+            ));
+        }
+    }
+
+    // Moves the walls to the arena, and the paddle, back to its size, to the middle of its floor
+    pub fn fit_arena(
+        config: Res<BreakoutConfig>,
+        arena: Res<Arena>,
+        mut wall_query: Query<(&WallLocation, &mut Transform), Without<Paddle>>,
+        paddle: Single<(&mut Transform, &mut PaddleWidth), With<Paddle>>,
+    ) {
+        let (mut paddle_transform, mut paddle_width) = paddle.into_inner();
+        for (location, mut wall_transform) in &mut wall_query {
+            wall_transform.translation = location.position(&arena).extend(0.0);
+            wall_transform.scale = location.size(&arena).extend(1.0);
+        }
+
+        paddle_transform.translation.x = arena.center().x;
+        paddle_transform.translation.y = arena.paddle_y();
+        paddle_width.a = 1.0;
+        paddle_transform.scale.x = config.paddle_size.x;
+    }
+
+    // Power-ups don't carry over to the next level
+    pub fn reset_power_ups(mut power_ups: ResMut<PowerUps>) {
+        *power_ups = PowerUps::default();
+    }
+
+    // A ball waiting on the paddle
+    pub fn serve_ball(
+        mut commands: Commands,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+        config: Res<BreakoutConfig>,
+        paddle_transform: Single<&Transform, With<Paddle>>,
+    ) {
+        commands.spawn(BallBundle::served(
+            &mut meshes,
+            &mut materials,
+            &config,
+            paddle_transform.translation,
+            config.ball_speed,
+        ));
+    }
+
+    pub fn move_paddle(
+        action_input: ActionInput,
+        config: Res<BreakoutConfig>,
+        arena: Res<Arena>,
+        mut paddle_transform: Single<&mut Transform, With<Paddle>>,
         time: Res<Time>,
+    ) {
+        // The paddle only moves sideways
+        let direction = action_input.axis(AxisAction::Thrust).x;
+
+        // Calculate the new horizontal paddle position based on player input
+        let new_paddle_position =
+            paddle_transform.translation.x + direction * config.paddle_speed * time.delta_secs();
+
+        // Update the paddle position,
+        // making sure it doesn't cause the paddle to leave the arena
+        let half_width = paddle_transform.scale.x / 2.0;
+        let left_bound = arena.left + WALL_THICKNESS / 2.0 + half_width + config.paddle_padding;
+        let right_bound = arena.right - WALL_THICKNESS / 2.0 - half_width - config.paddle_padding;
+
+        paddle_transform.translation.x = new_paddle_position.clamp(left_bound, right_bound);
+    }
+
+    // Served balls ride on the paddle until they're launched
+    pub fn carry_served_balls(
+        config: Res<BreakoutConfig>,
+        paddle_transform: Single<&Transform, (With<Paddle>, Without<Ball>)>,
+        mut ball_query: Query<(&mut Transform, &Served), With<Ball>>,
+    ) {
+        for (mut ball_transform, served) in &mut ball_query {
+            ball_transform.translation.x = paddle_transform.translation.x + served.offset;
+            ball_transform.translation.y = paddle_transform.translation.y + config.serve_height();
+        }
+    }
+
+    pub fn launch_ball(
+        mut commands: Commands,
+        action_input: ActionInput,
+        mut ball_query: Query<(Entity, &mut Velocity, &Speed, &Served), With<Ball>>,
+    ) {
+        if !action_input.just_pressed(ButtonAction::Confirm) {
+            return;
+        }
+
+        for (ball_entity, mut ball_velocity, ball_speed, served) in &mut ball_query {
+            ball_velocity.0 = served.direction * ball_speed.a;
+            commands.entity(ball_entity).remove::<Served>();
+        }
+    }
+
+    // Bounces only turn what moves, it keeps its own speed. Balls go slower while
+    // the slow ball power-up lasts.
+    pub fn match_speed(
+        power_ups: Res<PowerUps>,
+        config: Res<BreakoutConfig>,
+        mut speed_query: Query<(&mut Velocity, &Speed, Has<Ball>)>,
+    ) {
+        let slow_ball = power_ups.is_active(PowerUp::SlowBall);
+
+        for (mut velocity, speed, is_ball) in &mut speed_query {
+            let speed = if is_ball && slow_ball {
+                speed.a * config.slow_ball_multiplier
+            } else {
+                speed.a
+            };
+            velocity.0 = velocity.normalize_or_zero() * speed;
+        }
+    }
+
+    pub fn tick_power_ups(time: Res<Time>, mut power_ups: ResMut<PowerUps>) {
+        for remaining in &mut power_ups.remaining {
+            *remaining = (*remaining - time.delta_secs()).max(0.0);
+        }
+    }
+
+    // The paddle is as wide as bricks made it, and wider with the wide paddle power-up
+    pub fn size_paddle(
+        power_ups: Res<PowerUps>,
+        config: Res<BreakoutConfig>,
+        paddle: Single<(&mut Transform, &PaddleWidth)>,
+    ) {
+        let (mut paddle_transform, paddle_width) = paddle.into_inner();
+        let width = config.paddle_size.x * paddle_width.a;
+        paddle_transform.scale.x = if power_ups.is_active(PowerUp::WidePaddle) {
+            width * config.wide_paddle_multiplier
+        } else {
+            width
+        };
+        paddle_transform.scale.y = config.paddle_size.y;
+    }
+
+    // The paddle fires while it has the laser power-up
+    pub fn spawn_bullets(
         mut commands: Commands,
-        keyboard_input: Res<ButtonInput<KeyCode>>,
+        action_input: ActionInput,
+        config: Res<BreakoutConfig>,
+        power_ups: Res<PowerUps>,
         mut meshes: ResMut<Assets<Mesh>>,
         mut materials: ResMut<Assets<ColorMaterial>>,
         mut paddle_transform: Query<(&Transform, &mut BulletCooldown), With<Paddle>>,
     ) {
+        if !power_ups.is_active(PowerUp::LaserPaddle) {
+            return;
+        }
+
         for (paddle_transform, mut bullet_cooldown) in &mut paddle_transform {
-            bullet_cooldown.tick(time.delta());
-            if keyboard_input.pressed(KeyCode::Space) {
-                info!("Bullet timer: {:.2}", bullet_cooldown.elapsed_secs());
-                if bullet_cooldown.finished() {
-                    // Spawn a bullet at the paddle's position
-                    let bullet_position =
-                        paddle_transform.translation + Vec3::new(0.0, PADDLE_SIZE.y / 2.0, 0.0);
+            bullet_cooldown.set_duration(config.bullet_cooldown);
+            if action_input.pressed(ButtonAction::FireBullet) && bullet_cooldown.finished() {
+                // Spawn a bullet at each end of the paddle
+                let gun_x = paddle_transform.scale.x / 2.0 - config.bullet_diameter;
+                for x in [-gun_x, gun_x] {
+                    let bullet_position = paddle_transform.translation
+                        + Vec3::new(x, paddle_transform.scale.y / 2.0, 0.0);
 
                     commands.spawn(BulletBundle::new(
                         &mut meshes,
                         &mut materials,
+                        &config,
                         bullet_position,
+                        config.bullet_speed,
                     ));
-
-                    // Reset the bullet cooldown timer
-                    bullet_cooldown.reset();
                 }
+
+                // Reset the bullet cooldown timer
+                bullet_cooldown.reset();
             }
         }
     }
 
     pub fn update_scoreboard(
         score: Res<Score>,
-        score_root: Single<Entity, (With<ScoreboardUi>, With<Text>)>,
+        lives: Res<Lives>,
+        levels: Res<Levels>,
+        power_ups: Res<PowerUps>,
+        scoreboard: Single<&Children, With<ScoreboardUi>>,
         mut writer: TextUiWriter,
     ) {
-        *writer.text(*score_root, 1) = score.to_string();
+        *writer.text(scoreboard[0], 1) = score.to_string();
+        *writer.text(scoreboard[1], 1) = lives.to_string();
+        *writer.text(scoreboard[2], 1) = (levels.current + 1).to_string();
+        *writer.text(scoreboard[3], 1) = power_ups.describe();
     }
 
+    // Balls move themselves, from one bounce to the next: each step goes as far as the first
+    // thing the ball touches on its way, so fast balls can't skip through thin bricks
     pub fn check_for_ball_collisions(
         mut commands: Commands,
-        mut meshes: ResMut<Assets<Mesh>>,
-        mut materials: ResMut<Assets<ColorMaterial>>,
-        mut score: ResMut<Score>,
-        mut ball_speed: ResMut<Speed>,
-        mut ball_query: Query<(&mut Velocity, &Transform), With<Ball>>,
-        collider_query: Query<(Entity, &Transform, Option<&Brick>), With<Collider>>,
+        config: Res<BreakoutConfig>,
+        power_ups: Res<PowerUps>,
+        time: Res<Time>,
+        mut ball_query: Query<(Entity, &mut Transform, &mut Velocity, &Bounds), With<Ball>>,
+        mut collider_query: Query<
+            (
+                Entity,
+                &Bounds,
+                Option<&mut Brick>,
+                Has<Paddle>,
+                Has<KillZone>,
+            ),
+            (With<Collider>, Without<Broken>),
+        >,
         mut ball_collision_events: EventWriter<BallCollisionEvent>,
     ) {
-        for (mut ball_velocity, ball_transform) in &mut ball_query {
-            for (collider_entity, collider_transform, maybe_brick) in &collider_query {
-                let collision = collision(
-                    BoundingCircle::new(ball_transform.translation.truncate(), BALL_DIAMETER / 2.),
-                    Aabb2d::new(
-                        collider_transform.translation.truncate(),
-                        collider_transform.scale.truncate() / 2.,
-                    ),
-                );
-
-                if let Some(collision) = collision {
-                    // Writes a collision event so that other systems can react to the collision
-                    ball_collision_events.write_default();
-
-                    if let Some(brick) = maybe_brick {
-                        // Bricks should be despawned and increment the scoreboard on collision
-                        commands.entity(collider_entity).despawn();
-                        **score += 1;
-
-                        // If the brick was of type Speed, increase the ball speed
-                        match brick.r#type {
-                            BrickType::Normal => {}
-                            BrickType::Speed => {
-                                ball_speed.a *= BALL_SPEED_MULTIPLIER;
-                            }
-                            BrickType::ExtraBall => {
-                                // If the brick was of type ExtraBall, spawn a new ball
-                                commands.spawn(BallBundle::new(
-                                    &mut meshes,
-                                    &mut materials,
-                                    &ball_speed,
-                                ));
-                            }
-                        }
-                    }
+        // Bricks broken in this run are out of the way too, before their `Broken` is in
+        let mut broken = EntityHashSet::default();
+
+        for (ball_entity, mut ball_transform, mut ball_velocity, ball_bounds) in &mut ball_query {
+            let mut ball_circle = ball_bounds.bounding_circle();
+            let mut remaining_secs = time.delta_secs();
+
+            for _ in 0..BALL_MAX_BOUNCES_PER_TICK {
+                let motion = ball_velocity.0 * remaining_secs;
+                let first_hit = collider_query
+                    .iter()
+                    .filter(|(collider_entity, ..)| !broken.contains(collider_entity))
+                    .filter_map(|(collider_entity, collider_bounds, _, _, _)| {
+                        let (time, collision) = sweep(ball_circle, motion, collider_bounds.aabb())?;
+                        Some((time, collision, collider_entity))
+                    })
+                    .min_by(|a, b| a.0.total_cmp(&b.0));
+
+                let Some((hit_time, collision, collider_entity)) = first_hit else {
+                    ball_circle.center += motion;
+                    break;
+                };
+                ball_circle.center += motion * hit_time;
+                remaining_secs *= 1.0 - hit_time;
+
+                let (_, collider_bounds, maybe_brick, is_paddle, is_kill_zone) =
+                    collider_query.get_mut(collider_entity).unwrap();
+
+                // However fast it goes, a ball that reaches the kill zone stops there, lost
+                if is_kill_zone {
+                    commands.entity(ball_entity).insert(Lost);
+                    break;
+                }
 
-                    // Reflect the ball's velocity when it collides
-                    let mut reflect_x = false;
-                    let mut reflect_y = false;
-
-                    // Reflect only if the velocity is in the opposite direction of the collision
-                    // This prevents the ball from getting stuck inside the bar
-                    match collision {
-                        Collision::Left => reflect_x = ball_velocity.x > 0.0,
-                        Collision::Right => reflect_x = ball_velocity.x < 0.0,
-                        Collision::Top => reflect_y = ball_velocity.y < 0.0,
-                        Collision::Bottom => reflect_y = ball_velocity.y > 0.0,
-                    }
+                // Writes a collision event so that other systems can react to the collision
+                ball_collision_events.write_default();
+
+                // Broken bricks go off once all the hits are in
+                if let Some(mut brick) = maybe_brick
+                    && brick.hit()
+                {
+                    broken.insert(collider_entity);
+                    commands.entity(collider_entity).insert(Broken {
+                        by: Some(ball_entity),
+                    });
+                }
 
-                    // Reflect velocity on the x-axis if we hit something on the x-axis
-                    if reflect_x {
-                        ball_velocity.x = -ball_velocity.x;
-                    }
+                // The sweep only hits sides the ball moves into, so it always turns back
+                match collision {
+                    Collision::Left | Collision::Right => ball_velocity.x = -ball_velocity.x,
+                    Collision::Top | Collision::Bottom => ball_velocity.y = -ball_velocity.y,
+                }
 
-                    // Reflect velocity on the y-axis if we hit something on the y-axis
-                    if reflect_y {
-                        ball_velocity.y = -ball_velocity.y;
+                // Off the top of the paddle, where the ball lands decides where it goes
+                if is_paddle && collision == Collision::Top {
+                    let paddle = collider_bounds.aabb();
+                    let offset = (ball_circle.center.x - paddle.center().x) / paddle.half_size().x;
+                    ball_velocity.0 =
+                        paddle_bounce_direction(offset, config.paddle_bounce_max_angle)
+                            * ball_velocity.length();
+
+                    // The sticky paddle holds the ball where it landed, until it's launched
+                    if power_ups.is_active(PowerUp::StickyPaddle) {
+                        commands.entity(ball_entity).insert(Served {
+                            offset: offset * paddle.half_size().x,
+                            direction: ball_velocity.normalize(),
+                        });
+                        ball_velocity.0 = Vec2::ZERO;
                     }
                 }
             }
+
+            ball_transform.translation.x = ball_circle.center.x;
+            ball_transform.translation.y = ball_circle.center.y;
         }
     }
 
+    // Direction of a ball bouncing off the paddle `offset` half widths right of its middle.
+    // Straight up from the middle, `max_angle` degrees to the side from the edges.
+    pub fn paddle_bounce_direction(offset: f32, max_angle: f32) -> Vec2 {
+        let angle = (offset.clamp(-1.0, 1.0) * max_angle).to_radians();
+        Vec2::new(angle.sin(), angle.cos())
+    }
+
+    // Bullets move themselves too, and stop at the first thing on their way
     pub fn check_for_bullet_collisions(
         mut commands: Commands,
-        mut score: ResMut<Score>,
-        bullet_query: Query<(Entity, &Transform), With<Bullet>>,
-        collider_query: Query<
-            (Entity, &Transform, Option<&Brick>),
-            (With<Collider>, Without<Paddle>),
+        time: Res<Time>,
+        mut bullet_query: Query<(Entity, &mut Transform, &Velocity, &Bounds), With<Bullet>>,
+        mut collider_query: Query<
+            (Entity, &Bounds, Option<&mut Brick>),
+            (With<Collider>, Without<Paddle>, Without<Broken>),
         >,
         mut bullet_collision_events: EventWriter<BallCollisionEvent>,
     ) {
-        for (bullet_entity, bullet_transform) in bullet_query {
-            for (collider_entity, collider_transform, maybe_brick) in &collider_query {
-                let collision = collision(
-                    BoundingCircle::new(
-                        bullet_transform.translation.truncate(),
-                        BULLET_DIAMETER / 2.,
-                    ),
-                    Aabb2d::new(
-                        collider_transform.translation.truncate(),
-                        collider_transform.scale.truncate() / 2.,
-                    ),
-                );
-
-                if collision.is_some() {
-                    // Writes a collision event so that other systems can react to the collision
-                    bullet_collision_events.write_default();
-
-                    if maybe_brick.is_some() {
-                        // Bricks should be despawned and increment the scoreboard on collision
-                        commands.entity(collider_entity).despawn();
-                        **score += 1;
+        let mut broken = EntityHashSet::default();
+
+        for (bullet_entity, mut bullet_transform, bullet_velocity, bullet_bounds) in
+            &mut bullet_query
+        {
+            let motion = bullet_velocity.0 * time.delta_secs();
+            let first_hit = collider_query
+                .iter()
+                .filter(|(collider_entity, ..)| !broken.contains(collider_entity))
+                .filter_map(|(collider_entity, collider_bounds, _)| {
+                    let (time, _) = sweep(
+                        bullet_bounds.bounding_circle(),
+                        motion,
+                        collider_bounds.aabb(),
+                    )?;
+                    Some((time, collider_entity))
+                })
+                .min_by(|a, b| a.0.total_cmp(&b.0));
+
+            let Some((_, collider_entity)) = first_hit else {
+                bullet_transform.translation += motion.extend(0.0);
+                continue;
+            };
+
+            // Writes a collision event so that other systems can react to the collision
+            bullet_collision_events.write_default();
+
+            if let Ok((_, _, Some(mut brick))) = collider_query.get_mut(collider_entity)
+                && brick.hit()
+            {
+                broken.insert(collider_entity);
+                commands.entity(collider_entity).insert(Broken { by: None });
+            }
+            commands.entity(bullet_entity).despawn();
+        }
+    }
+
+    // Broken bricks go off: they score, apply their effect and are despawned.
+    // Explosive bricks break the bricks around them, which go off in turn.
+    pub fn break_bricks(
+        mut commands: Commands,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+        mut score: ResMut<Score>,
+        config: Res<BreakoutConfig>,
+        mut rng: ResMut<GameRng>,
+        mut paddle_width: Single<&mut PaddleWidth>,
+        mut ball_query: Query<&mut Speed, With<Ball>>,
+        brick_query: Query<(Entity, &Brick, &Transform, Option<&Broken>)>,
+    ) {
+        // Bricks an explosion breaks count as broken by the ball that broke the explosive one
+        let mut going_off = brick_query
+            .iter()
+            .filter_map(|(brick_entity, _, _, broken)| Some((brick_entity, broken?.by)))
+            .collect::<Vec<_>>();
+        let mut gone = going_off
+            .iter()
+            .map(|(brick_entity, _)| *brick_entity)
+            .collect::<EntityHashSet>();
+
+        while let Some((brick_entity, broken_by)) = going_off.pop() {
+            let Ok((_, brick, brick_transform, _)) = brick_query.get(brick_entity) else {
+                continue;
+            };
+            commands.entity(brick_entity).despawn();
+            **score += 1;
+
+            if rng.unit() < config.capsule_drop_chance {
+                commands.spawn(CapsuleBundle::new(
+                    &config,
+                    brick_transform.translation.truncate(),
+                    PowerUp::random(&config, &mut rng),
+                    config.capsule_fall_speed,
+                ));
+            }
+
+            match brick.r#type {
+                BrickType::Normal | BrickType::Tough | BrickType::Indestructible => {}
+                // Only the ball that broke it speeds up
+                BrickType::Speed => {
+                    if let Some(mut ball_speed) =
+                        broken_by.and_then(|ball| ball_query.get_mut(ball).ok())
+                    {
+                        ball_speed.a = (ball_speed.a * config.ball_speed_multiplier)
+                            .min(config.ball_max_speed);
+                    }
+                }
+                BrickType::ExtraBall => {
+                    commands.spawn(BallBundle::new(
+                        &mut meshes,
+                        &mut materials,
+                        &config,
+                        config.ball_speed,
+                    ));
+                }
+                BrickType::Explosive => {
+                    let center = brick_transform.translation.truncate();
+                    for (other_entity, other_brick, other_transform, _) in &brick_query {
+                        let in_reach = other_transform.translation.truncate().distance(center)
+                            <= config.explosion_radius;
+                        if in_reach
+                            && other_brick.r#type != BrickType::Indestructible
+                            && gone.insert(other_entity)
+                        {
+                            going_off.push((other_entity, broken_by));
+                        }
+                    }
+                }
+                BrickType::PaddleGrow => {
+                    paddle_width.a = (paddle_width.a * config.paddle_resize_multiplier)
+                        .min(config.paddle_max_width / config.paddle_size.x);
+                }
+                BrickType::PaddleShrink => {
+                    paddle_width.a = (paddle_width.a / config.paddle_resize_multiplier)
+                        .max(config.paddle_min_width / config.paddle_size.x);
+                }
+            }
+        }
+    }
+
+    // Capsules caught by the paddle give it their power-up, the others fall into the kill zone
+    pub fn catch_capsules(
+        mut commands: Commands,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+        config: Res<BreakoutConfig>,
+        mut power_ups: ResMut<PowerUps>,
+        capsule_query: Query<(Entity, &Capsule, &Bounds)>,
+        ball_query: Query<(&Transform, &Velocity, &Speed), (With<Ball>, Without<Served>)>,
+        paddle_bounds: Single<&Bounds, With<Paddle>>,
+        kill_zone_bounds: Single<&Bounds, With<KillZone>>,
+    ) {
+        for (capsule_entity, capsule, capsule_bounds) in &capsule_query {
+            if capsule_bounds.intersects(&paddle_bounds) {
+                commands.entity(capsule_entity).despawn();
+
+                if capsule.power_up.is_timed() {
+                    power_ups.start(capsule.power_up, config.power_up_duration);
+                    continue;
+                }
+
+                // Multi-ball, the balls in play each split off two more
+                let spread = config.multi_ball_spread.to_radians();
+                for (ball_transform, ball_velocity, ball_speed) in &ball_query {
+                    for angle in [-spread, spread] {
+                        commands.spawn(BallBundle::at(
+                            &mut meshes,
+                            &mut materials,
+                            &config,
+                            ball_transform.translation,
+                            Vec2::from_angle(angle).rotate(ball_velocity.normalize_or_zero()),
+                            ball_speed.a,
+                        ));
                     }
-                    info!("Bullet collided with {:?}", collider_entity);
-                    commands.entity(bullet_entity).despawn();
                 }
+            } else if capsule_bounds.intersects(&kill_zone_bounds) {
+                commands.entity(capsule_entity).despawn();
             }
         }
     }
 
-    // Returns `Some` if `ball` collides with `bounding_box`.
-    // The returned `Collision` is the side of `bounding_box` that `ball` hit.
-    fn collision(circular_object: BoundingCircle, bounding_box: Aabb2d) -> Option<Collision> {
-        if !circular_object.intersects(&bounding_box) {
-            return None;
+    // Bricks lighten as they take damage
+    pub fn color_bricks(
+        config: Res<BreakoutConfig>,
+        mut brick_query: Query<(&Brick, &mut Sprite), Changed<Brick>>,
+    ) {
+        for (brick, mut sprite) in &mut brick_query {
+            sprite.color = brick.color(&config);
+        }
+    }
+
+    // The paddle is sized every tick, by `size_paddle`
+    pub fn restyle_screens(
+        mut commands: Commands,
+        config: Res<BreakoutConfig>,
+        mut screen_style: ResMut<ScreenStyle>,
+        mut scoreboard_style: ResMut<ScoreboardStyle>,
+        mut text_query: Query<&mut TextColor>,
+        mut background_query: Query<&mut BackgroundColor>,
+    ) {
+        commands.insert_resource(ClearColor(config.background_color));
+        restyle_text(
+            config.text_colors(),
+            &mut screen_style,
+            &mut scoreboard_style,
+            &mut text_query,
+            &mut background_query,
+        );
+    }
+
+    pub fn restyle_colors(
+        config: Res<BreakoutConfig>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+        mut sprite_query: Query<(
+            &mut Sprite,
+            Option<&Brick>,
+            Option<&Capsule>,
+            Has<Wall>,
+            Has<Paddle>,
+        )>,
+        material_query: Query<
+            (&MeshMaterial2d<ColorMaterial>, Has<Ball>),
+            Or<(With<Ball>, With<Bullet>)>,
+        >,
+    ) {
+        for (mut sprite, brick, capsule, is_wall, is_paddle) in &mut sprite_query {
+            sprite.color = match (brick, capsule) {
+                (Some(brick), _) => brick.color(&config),
+                (_, Some(capsule)) => capsule.power_up.color(&config),
+                _ if is_wall => config.wall_color,
+                _ if is_paddle => config.paddle_color,
+                _ => continue,
+            };
+        }
+
+        for (material, is_ball) in &material_query {
+            if let Some(material) = materials.get_mut(material) {
+                material.color = if is_ball {
+                    config.ball_color
+                } else {
+                    config.bullet_color
+                };
+            }
+        }
+    }
+
+    pub fn restyle_sizes(
+        config: Res<BreakoutConfig>,
+        arena: Res<Arena>,
+        grid: Res<Grid>,
+        mut transform_query: Query<
+            (
+                &mut Transform,
+                Option<&GridCell>,
+                Has<Brick>,
+                Has<Capsule>,
+                Has<Ball>,
+            ),
+            Or<(With<Brick>, With<Capsule>, With<Ball>, With<Bullet>)>,
+        >,
+    ) {
+        for (mut transform, cell, is_brick, is_capsule, is_ball) in &mut transform_query {
+            let size = if is_brick {
+                config.brick_size
+            } else if is_capsule {
+                config.capsule_size
+            } else if is_ball {
+                Vec2::splat(config.ball_diameter)
+            } else {
+                Vec2::splat(config.bullet_diameter)
+            };
+            transform.scale = size.extend(1.0);
+
+            // Bricks of the level are laid out again around their new size
+            if let Some(cell) = cell {
+                let position = grid.position(&arena, *cell, config.brick_size);
+                transform.translation = position.extend(transform.translation.z);
+            }
         }
+    }
+
+    // Balls that reached the bottom are lost. Losing the last one costs a life and serves
+    // a new ball, or ends the game if that was the last life.
+    pub fn lose_balls(
+        mut commands: Commands,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+        config: Res<BreakoutConfig>,
+        mut lives: ResMut<Lives>,
+        mut next_state: ResMut<NextState<GameState>>,
+        ball_query: Query<(Entity, Has<Lost>), With<Ball>>,
+        paddle_transform: Single<&Transform, With<Paddle>>,
+    ) {
+        let mut lost_any = false;
+        let mut remaining = 0;
 
-        let closest = bounding_box.closest_point(circular_object.center());
-        let offset = circular_object.center() - closest;
-        let side = if offset.x.abs() > offset.y.abs() {
-            if offset.x < 0. {
-                Collision::Left
+        for (ball_entity, is_lost) in &ball_query {
+            if is_lost {
+                commands.entity(ball_entity).despawn();
+                lost_any = true;
             } else {
-                Collision::Right
+                remaining += 1;
             }
-        } else if offset.y > 0. {
-            Collision::Top
+        }
+
+        if !lost_any || remaining > 0 {
+            return;
+        }
+
+        **lives = lives.saturating_sub(1);
+        if **lives == 0 {
+            next_state.set(GameState::GameOver);
         } else {
-            Collision::Bottom
-        };
+            commands.spawn(BallBundle::served(
+                &mut meshes,
+                &mut materials,
+                &config,
+                paddle_transform.translation,
+                config.ball_speed,
+            ));
+        }
+    }
+
+    // Clearing every brick but the indestructible ones moves on to the next level,
+    // or wins the game after the last one
+    pub fn check_level_cleared(
+        brick_query: Query<&Brick>,
+        levels: Res<Levels>,
+        mut next_state: ResMut<NextState<GameState>>,
+    ) {
+        if brick_query
+            .iter()
+            .any(|brick| brick.r#type != BrickType::Indestructible)
+        {
+            return;
+        }
 
-        Some(side)
+        if levels.is_last() {
+            next_state.set(GameState::Victory);
+        } else {
+            next_state.set(GameState::LevelCleared);
+        }
     }
 
-    pub fn play_collision_sound(
+    pub fn advance_level(mut levels: ResMut<Levels>) {
+        levels.current += 1;
+    }
+
+    pub fn spawn_level_cleared_screen(
         mut commands: Commands,
-        mut collision_events: EventReader<BallCollisionEvent>,
-        sound: Res<CollisionSound>,
+        style: Res<ScreenStyle>,
+        levels: Res<Levels>,
+        level_assets: Res<Assets<Level>>,
+        bindings: Res<InputBindings>,
     ) {
-        // Play a sound once per frame if a collision occurred.
-        if !collision_events.is_empty() {
-            // This prevents events staying active on the next frame.
-            collision_events.clear();
-            commands.spawn((AudioPlayer(sound.clone()), PlaybackSettings::DESPAWN));
-        }
+        let next_level = level_assets
+            .get(&levels.a[levels.current])
+            .expect("levels stay loaded");
+
+        style.spawn(
+            &mut commands,
+            GameState::LevelCleared,
+            "Level Cleared",
+            &[
+                format!("Next: {}", next_level.name),
+                format!(
+                    "Press {} to continue",
+                    bindings.describe(ButtonAction::Confirm)
+                ),
+                settings_line(&bindings),
+            ],
+        );
     }
 
-    #[derive(Debug, PartialEq, Eq, Copy, Clone)]
-    enum Collision {
-        Left,
-        Right,
-        Top,
-        Bottom,
+    pub fn spawn_game_over_screen(
+        mut commands: Commands,
+        style: Res<ScreenStyle>,
+        score: Res<Score>,
+        bindings: Res<InputBindings>,
+    ) {
+        style.spawn(
+            &mut commands,
+            GameState::GameOver,
+            "Game Over",
+            &end_screen_lines(&score, &bindings),
+        );
+    }
+
+    pub fn spawn_victory_screen(
+        mut commands: Commands,
+        style: Res<ScreenStyle>,
+        score: Res<Score>,
+        bindings: Res<InputBindings>,
+    ) {
+        style.spawn(
+            &mut commands,
+            GameState::Victory,
+            "You Win",
+            &end_screen_lines(&score, &bindings),
+        );
+    }
+
+    fn end_screen_lines(score: &Score, bindings: &InputBindings) -> Vec<String> {
+        vec![
+            format!("Score: {}", score.a),
+            format!(
+                "Press {} to play again",
+                bindings.describe(ButtonAction::Confirm)
+            ),
+            settings_line(bindings),
+        ]
+    }
+
+    fn settings_line(bindings: &InputBindings) -> String {
+        format!(
+            "Press {} to change the controls",
+            bindings.describe(ButtonAction::Settings)
+        )
+    }
+
+    // Confirm leaves the screens between games and levels for the next one,
+    // and Settings opens the settings screen, which comes back to them
+    pub fn end_screen_input(
+        action_input: ActionInput,
+        mut next_state: ResMut<NextState<GameState>>,
+    ) {
+        if action_input.just_pressed(ButtonAction::Confirm) {
+            next_state.set(GameState::Playing);
+        } else if action_input.just_pressed(ButtonAction::Settings) {
+            next_state.set(GameState::Settings);
+        }
     }
 }
 
@@ -762,32 +2247,923 @@ fn main() {
     };
     App::new()
         .add_plugins(DefaultPlugins.set(window_plugin))
+        .add_plugins(BreakoutPlugin)
         // .add_plugins(
         //     stepping::SteppingPlugin::default()
         //         .add_schedule(Update)
         //         .add_schedule(FixedUpdate)
         //         .at(Val::Percent(35.0), Val::Percent(50.0)),
         // )
+        .run();
+}
+
+// The paddle, balls and bricks, and the levels they're played through
+pub struct BreakoutPlugin;
+
+impl Plugin for BreakoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(ActionInputPlugin {
+            path: INPUT_BINDINGS_PATH,
+        })
+        .add_plugins(ConfigPlugin::<BreakoutConfig>::new(CONFIG_PATH))
+        .add_plugins(RngPlugin)
+        // Inputs are recorded and played back by gameplay tick
+        .add_plugins(ReplayPlugin::new(FixedUpdate))
+        .add_plugins(CollisionSoundPlugin {
+            path: COLLISION_SOUND_PATH,
+        })
+        .add_plugins(ScoreboardPlugin {
+            style: ScoreboardStyle {
+                font_size: SCOREBOARD_FONT_SIZE,
+                padding: SCOREBOARD_TEXT_PADDING,
+                text_color: TEXT_COLOR,
+                value_color: SCORE_COLOR,
+            },
+        })
+        // Everything moves with the gameplay, one fixed tick at a time
+        .add_plugins((
+            CooldownPlugin::<BulletCooldown>::new(FixedUpdate),
+            VelocityPlugin::new(FixedUpdate),
+            BoundsPlugin::new(FixedUpdate),
+        ))
+        .add_plugins(ScreenPlugin {
+            style: ScreenStyle {
+                title_font_size: TITLE_FONT_SIZE,
+                line_font_size: SUBTITLE_FONT_SIZE,
+                row_gap: SCREEN_ROW_GAP,
+                overlay_color: SCREEN_OVERLAY_COLOR,
+                title_color: TITLE_COLOR,
+                text_color: TEXT_COLOR,
+            },
+        })
+        .add_plugins(SettingsPlugin {
+            state: GameState::Settings,
+            controls: SETTINGS_CONTROLS,
+        })
         .insert_resource(Score { a: 0 })
-        .insert_resource(ClearColor(BACKGROUND_COLOR))
-        .insert_resource(Speed::default())
-        .add_event::<BallCollisionEvent>()
-        .add_systems(Startup, setup)
+        .insert_resource(Lives { a: STARTING_LIVES })
+        .init_resource::<PowerUps>()
+        .init_resource::<Arena>()
+        .init_resource::<Grid>()
+        .init_asset::<Level>()
+        .init_asset_loader::<LevelLoader>()
+        .init_state::<GameState>()
+        .add_systems(Startup, (setup, load_levels))
+        .add_systems(
+            Update,
+            finish_loading.run_if(
+                in_state(GameState::Loading)
+                    .and(config_ready::<BreakoutConfig>)
+                    .and(levels_loaded),
+            ),
+        )
+        // Every way into `Playing` but from a cleared level starts a new game. The screens
+        // between games and levels are left for the settings too, which must not end them.
+        .add_systems(OnExit(GameState::Loading), start_game)
+        .add_systems(
+            OnTransition {
+                exited: GameState::GameOver,
+                entered: GameState::Playing,
+            },
+            start_game,
+        )
+        .add_systems(
+            OnTransition {
+                exited: GameState::Victory,
+                entered: GameState::Playing,
+            },
+            start_game,
+        )
+        .add_systems(
+            OnEnter(GameState::Playing),
+            (spawn_level, fit_arena, reset_power_ups, serve_ball).chain(),
+        )
+        .add_systems(
+            OnTransition {
+                exited: GameState::Playing,
+                entered: GameState::LevelCleared,
+            },
+            advance_level,
+        )
+        .add_systems(OnEnter(GameState::LevelCleared), spawn_level_cleared_screen)
+        .add_systems(OnEnter(GameState::GameOver), spawn_game_over_screen)
+        .add_systems(OnEnter(GameState::Victory), spawn_victory_screen)
+        .add_systems(
+            Update,
+            end_screen_input.run_if(
+                in_state(GameState::LevelCleared)
+                    .or(in_state(GameState::GameOver))
+                    .or(in_state(GameState::Victory)),
+            ),
+        )
         // Add our gameplay simulation systems to the fixed timestep schedule
         // which runs at 64 Hz by default
+        // Input acts on the cooled down paddle, then everything but the balls and bullets moves,
+        // and those sweep along their paths to find what they hit
+        .configure_sets(
+            FixedUpdate,
+            (
+                ReplaySet::Feed,
+                TickCooldowns,
+                IntegrateVelocity,
+                UpdateBounds,
+                ReplaySet::Release,
+            )
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        )
         .add_systems(
-            Update,
+            FixedUpdate,
             (
+                tick_power_ups,
                 spawn_bullets,
-                apply_velocity,
-                move_paddle,
+                (move_paddle, carry_served_balls, launch_ball).chain(),
+            )
+                .chain()
+                .after(TickCooldowns)
+                .before(IntegrateVelocity)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
                 check_for_ball_collisions,
                 check_for_bullet_collisions,
-                play_collision_sound,
+                break_bricks,
+                catch_capsules,
+                size_paddle,
+                color_bricks,
+                lose_balls,
+                check_level_cleared,
+                match_speed,
             )
                 // `chain`ing systems together runs them in order
-                .chain(),
+                .chain()
+                .after(UpdateBounds)
+                .before(ReplaySet::Release)
+                .run_if(in_state(GameState::Playing)),
         )
-        .add_systems(Update, update_scoreboard)
-        .run();
+        .add_systems(
+            Update,
+            (restyle_screens, restyle_colors, restyle_sizes)
+                .run_if(resource_changed::<BreakoutConfig>),
+        )
+        .add_systems(Update, update_scoreboard);
+    }
+}
+
+// Whole game tests, run headless one fixed tick at a time
+#[cfg(test)]
+mod tests {
+    use super::bundles::{BallBundle, BrickBundle, CapsuleBundle};
+    use super::*;
+    use bevy_sandbox::headless::*;
+
+    // A game with the default controls, once the bricks are in,
+    // and no capsules so the tests bring their own
+    fn new_game() -> App {
+        let mut app = headless_app();
+        app.add_plugins(BreakoutPlugin);
+
+        step_until_config_ready::<BreakoutConfig>(&mut app);
+        let started = step_until(&mut app, LOADING_TICKS, |world| {
+            world.query::<&Brick>().iter(world).next().is_some()
+        });
+        assert!(started, "the bricks never spawned");
+
+        let world = app.world_mut();
+        *world.resource_mut::<InputBindings>() = InputBindings::default();
+        world.resource_mut::<BreakoutConfig>().capsule_drop_chance = 0.0;
+        app
+    }
+
+    fn paddle(app: &mut App) -> Transform {
+        let world = app.world_mut();
+        *world
+            .query_filtered::<&Transform, With<Paddle>>()
+            .single(world)
+            .unwrap()
+    }
+
+    // A capsule right on top of the paddle
+    fn catch_capsule(app: &mut App, power_up: PowerUp) {
+        let position = paddle(app).translation.truncate() + Vec2::Y * PADDLE_SIZE.y;
+        let config = app.world().resource::<BreakoutConfig>().clone();
+        app.world_mut().spawn(CapsuleBundle::new(
+            &config,
+            position,
+            power_up,
+            CAPSULE_FALL_SPEED,
+        ));
+
+        let caught = step_until(app, 10, |world| {
+            world.query::<&Capsule>().iter(world).next().is_none()
+        });
+        assert!(caught, "the capsule was never caught");
+    }
+
+    // A brick styled by the default config
+    fn brick_bundle(position: Vec2, r#type: BrickType, hit_points: u32) -> BrickBundle {
+        BrickBundle::new(&BreakoutConfig::default(), position, r#type, hit_points)
+    }
+
+    fn count<T: Component>(app: &mut App) -> usize {
+        let world = app.world_mut();
+        world.query_filtered::<(), With<T>>().iter(world).count()
+    }
+
+    fn despawn_all<T: Component>(app: &mut App) {
+        let world = app.world_mut();
+        let entities = world
+            .query_filtered::<Entity, With<T>>()
+            .iter(world)
+            .collect::<Vec<_>>();
+        for entity in entities {
+            world.despawn(entity);
+        }
+    }
+
+    fn spawn_ball(app: &mut App, position: Vec2, velocity: Vec2) -> Entity {
+        let config = app.world().resource::<BreakoutConfig>().clone();
+        spawn_with_assets(app, move |meshes, materials| {
+            BallBundle::at(
+                meshes,
+                materials,
+                &config,
+                position.extend(1.0),
+                velocity.normalize_or_zero(),
+                velocity.length(),
+            )
+        })
+    }
+
+    // A ball heading straight into the bottom wall, away from the paddle
+    fn drop_ball(app: &mut App) {
+        despawn_all::<Ball>(app);
+        spawn_ball(
+            app,
+            Vec2::new(RIGHT_WALL / 2.0, BOTTOM_WALL + BALL_DIAMETER),
+            Vec2::NEG_Y * BALL_SPEED,
+        );
+    }
+
+    fn state(app: &App) -> GameState {
+        *app.world().resource::<State<GameState>>().get()
+    }
+
+    #[test]
+    fn extra_ball_brick_spawns_a_ball() {
+        let mut app = new_game();
+        despawn_all::<Brick>(&mut app);
+        despawn_all::<Ball>(&mut app);
+
+        // A ball with an extra ball brick right in its way, and a brick out of the way
+        // so the game isn't won
+        spawn_ball(
+            &mut app,
+            BALL_STARTING_POSITION.truncate(),
+            INITIAL_BALL_DIRECTION.normalize() * BALL_SPEED,
+        );
+        let brick_position = BALL_STARTING_POSITION.truncate()
+            + INITIAL_BALL_DIRECTION.normalize() * (BALL_DIAMETER + BRICK_SIZE.y);
+        let world = app.world_mut();
+        world.spawn(brick_bundle(brick_position, BrickType::ExtraBall, 1));
+        world.spawn(brick_bundle(
+            Vec2::new(LEFT_WALL, TOP_WALL) / 2.0,
+            BrickType::Normal,
+            1,
+        ));
+
+        let spawned = step_until(&mut app, 60, |world| {
+            world.query_filtered::<(), With<Ball>>().iter(world).count() == 2
+        });
+
+        assert!(spawned, "no ball came out of the brick");
+        assert_eq!(count::<Brick>(&mut app), 1);
+    }
+
+    #[test]
+    fn paddle_cannot_leave_the_arena() {
+        let mut app = new_game();
+        let padding = app.world().resource::<BreakoutConfig>().paddle_padding;
+        let left_bound = LEFT_WALL + WALL_THICKNESS / 2.0 + PADDLE_SIZE.x / 2.0 + padding;
+
+        press(&mut app, KeyCode::ArrowLeft);
+        step(&mut app, 600);
+
+        let paddle = paddle(&mut app);
+        assert!(paddle.translation.x >= left_bound);
+        assert!(
+            paddle.translation.x - left_bound < 1.0,
+            "the paddle never reached the wall"
+        );
+    }
+
+    #[test]
+    fn paddle_bounce_follows_the_hit_position() {
+        let mut app = new_game();
+        despawn_all::<Ball>(&mut app);
+        let max_angle = app
+            .world()
+            .resource::<BreakoutConfig>()
+            .paddle_bounce_max_angle;
+
+        // Halfway between the middle and the right edge of the paddle
+        let paddle_top = BOTTOM_WALL + GAP_BETWEEN_PADDLE_AND_FLOOR + PADDLE_SIZE.y / 2.0;
+        spawn_ball(
+            &mut app,
+            Vec2::new(PADDLE_SIZE.x / 4.0, paddle_top + BALL_DIAMETER),
+            Vec2::NEG_Y * BALL_SPEED,
+        );
+        let bounced = step_until(&mut app, 60, |world| {
+            world
+                .query_filtered::<&Velocity, With<Ball>>()
+                .single(world)
+                .is_ok_and(|velocity| velocity.y > 0.0)
+        });
+        assert!(bounced, "the ball never bounced");
+
+        let world = app.world_mut();
+        let velocity = world
+            .query_filtered::<&Velocity, With<Ball>>()
+            .single(world)
+            .unwrap();
+        let angle = velocity.angle_to(Vec2::Y).to_degrees();
+        assert!((angle - max_angle / 2.0).abs() < 1.0, "bounced at {angle}");
+    }
+
+    #[test]
+    fn paddle_bounce_never_goes_past_the_max_angle() {
+        assert_eq!(paddle_bounce_direction(0.0, 60.0), Vec2::Y);
+
+        let edge = paddle_bounce_direction(-3.0, 60.0);
+        assert!((edge.angle_to(Vec2::Y).to_degrees() + 60.0).abs() < 1e-3);
+        assert!(edge.y > 0.0);
+    }
+
+    #[test]
+    fn served_ball_waits_for_the_launch() {
+        let mut app = new_game();
+        step(&mut app, 30);
+        assert_eq!(count::<Served>(&mut app), 1);
+
+        press(&mut app, KeyCode::Enter);
+        app.update();
+
+        let world = app.world_mut();
+        let velocity = world
+            .query_filtered::<&Velocity, With<Ball>>()
+            .single(world)
+            .unwrap();
+        assert!(velocity.y > 0.0);
+        assert_eq!(count::<Served>(&mut app), 0);
+    }
+
+    #[test]
+    fn losing_the_last_ball_costs_a_life() {
+        let mut app = new_game();
+        let starting_lives = app.world().resource::<Lives>().a;
+
+        drop_ball(&mut app);
+        let lost = step_until(&mut app, 60, |world| {
+            world.resource::<Lives>().a < starting_lives
+        });
+
+        assert!(lost, "the ball was never lost");
+        assert_eq!(app.world().resource::<Lives>().a, starting_lives - 1);
+        assert_eq!(count::<Served>(&mut app), 1);
+        assert_eq!(state(&app), GameState::Playing);
+    }
+
+    #[test]
+    fn fast_balls_cannot_skip_the_kill_zone() {
+        let mut app = new_game();
+        despawn_all::<Ball>(&mut app);
+
+        // In one tick, the ball goes further than the kill zone and itself are thick
+        spawn_ball(
+            &mut app,
+            Vec2::new(RIGHT_WALL / 2.0, BOTTOM_WALL + BALL_DIAMETER),
+            Vec2::NEG_Y * 20_000.0,
+        );
+        step(&mut app, 2);
+
+        assert_eq!(**app.world().resource::<Lives>(), STARTING_LIVES - 1);
+    }
+
+    #[test]
+    fn losing_the_last_life_ends_the_game() {
+        let mut app = new_game();
+        app.world_mut().resource_mut::<Lives>().a = 1;
+
+        drop_ball(&mut app);
+        let over = step_until(&mut app, 60, |world| {
+            *world.resource::<State<GameState>>().get() == GameState::GameOver
+        });
+
+        assert!(over, "the game went on");
+        assert_eq!(count::<Ball>(&mut app), 0);
+    }
+
+    #[test]
+    fn bricks_take_their_hit_points_to_break() {
+        let mut app = new_game();
+        despawn_all::<Brick>(&mut app);
+        despawn_all::<Ball>(&mut app);
+
+        // A ball going straight up into a brick that takes two hits
+        let brick = app
+            .world_mut()
+            .spawn(brick_bundle(Vec2::ZERO, BrickType::Normal, 2))
+            .id();
+        let full_color = app.world().get::<Sprite>(brick).unwrap().color;
+        spawn_ball(
+            &mut app,
+            Vec2::new(0.0, -BRICK_SIZE.y - BALL_DIAMETER),
+            Vec2::Y * BALL_SPEED,
+        );
+        let hit = step_until(&mut app, 60, |world| {
+            world.get::<Brick>(brick).unwrap().hit_points < 2
+        });
+
+        assert!(hit, "the ball never hit the brick");
+        app.update();
+        assert_eq!(app.world().resource::<Score>().a, 0);
+        assert_ne!(app.world().get::<Sprite>(brick).unwrap().color, full_color);
+        assert_eq!(state(&app), GameState::Playing);
+    }
+
+    // Breaks a brick of `r#type` at `position` without a ball, with another brick
+    // out of the way so the level isn't cleared
+    fn break_brick(app: &mut App, position: Vec2, r#type: BrickType) {
+        let world = app.world_mut();
+        world.spawn((brick_bundle(position, r#type, 1), Broken { by: None }));
+        world.spawn(brick_bundle(
+            Vec2::new(LEFT_WALL, TOP_WALL) / 2.0,
+            BrickType::Normal,
+            1,
+        ));
+        app.update();
+    }
+
+    #[test]
+    fn explosive_bricks_break_their_neighbours() {
+        let mut app = new_game();
+        despawn_all::<Brick>(&mut app);
+
+        let step_x = Vec2::X * (BRICK_SIZE.x + GAP_BETWEEN_BRICKS);
+        let world = app.world_mut();
+        let neighbour = world.spawn(brick_bundle(step_x, BrickType::Tough, 3)).id();
+        let next_explosive = world
+            .spawn(brick_bundle(-step_x, BrickType::Explosive, 1))
+            .id();
+        let chained = world
+            .spawn(brick_bundle(-step_x * 2.0, BrickType::Normal, 1))
+            .id();
+        let unbreakable = world
+            .spawn(brick_bundle(step_x * 0.5, BrickType::Indestructible, 1))
+            .id();
+        let far = world
+            .spawn(brick_bundle(step_x * 4.0, BrickType::Normal, 1))
+            .id();
+
+        break_brick(&mut app, Vec2::ZERO, BrickType::Explosive);
+
+        let world = app.world();
+        for gone in [neighbour, next_explosive, chained] {
+            assert!(world.get_entity(gone).is_err());
+        }
+        for kept in [unbreakable, far] {
+            assert!(world.get_entity(kept).is_ok());
+        }
+        assert_eq!(world.resource::<Score>().a, 4);
+    }
+
+    #[test]
+    fn paddle_bricks_resize_the_paddle() {
+        let mut app = new_game();
+        despawn_all::<Brick>(&mut app);
+        let config = app.world().resource::<BreakoutConfig>().clone();
+
+        break_brick(&mut app, Vec2::ZERO, BrickType::PaddleGrow);
+        assert_eq!(
+            paddle(&mut app).scale.x,
+            PADDLE_SIZE.x * config.paddle_resize_multiplier
+        );
+
+        for _ in 0..10 {
+            break_brick(&mut app, Vec2::ZERO, BrickType::PaddleShrink);
+        }
+        assert_eq!(paddle(&mut app).scale.x, config.paddle_min_width);
+    }
+
+    // Sends a ball of `speed` straight up into a speed brick, next to a ball going sideways,
+    // and returns the speeds of both once the brick is broken
+    fn break_speed_brick(app: &mut App, speed: f32) -> (f32, f32) {
+        despawn_all::<Brick>(app);
+        despawn_all::<Ball>(app);
+
+        let world = app.world_mut();
+        let brick = world
+            .spawn(brick_bundle(Vec2::ZERO, BrickType::Speed, 1))
+            .id();
+        world.spawn(brick_bundle(
+            Vec2::new(LEFT_WALL, TOP_WALL) / 2.0,
+            BrickType::Normal,
+            1,
+        ));
+        let hitting = spawn_ball(
+            app,
+            Vec2::new(0.0, -BRICK_SIZE.y - BALL_DIAMETER),
+            Vec2::Y * speed,
+        );
+        let other = spawn_ball(app, Vec2::new(RIGHT_WALL / 2.0, 0.0), Vec2::X * BALL_SPEED);
+
+        let broken = step_until(app, 60, |world| world.get_entity(brick).is_err());
+        assert!(broken, "the ball never broke the brick");
+
+        let world = app.world();
+        (
+            world.get::<Speed>(hitting).unwrap().a,
+            world.get::<Speed>(other).unwrap().a,
+        )
+    }
+
+    #[test]
+    fn fast_balls_bounce_off_bricks_they_would_jump_over() {
+        let mut app = new_game();
+        despawn_all::<Brick>(&mut app);
+        despawn_all::<Ball>(&mut app);
+
+        let world = app.world_mut();
+        let brick = world
+            .spawn(brick_bundle(Vec2::ZERO, BrickType::Normal, 1))
+            .id();
+        world.spawn(brick_bundle(
+            Vec2::new(LEFT_WALL, TOP_WALL) / 2.0,
+            BrickType::Normal,
+            1,
+        ));
+
+        // In one tick, the ball goes further than the brick and itself are thick
+        let start = Vec2::new(0.0, -BRICK_SIZE.y / 2.0 - BALL_DIAMETER / 2.0 - 10.0);
+        let ball = spawn_ball(&mut app, start, Vec2::Y * 6000.0);
+        step(&mut app, 1);
+
+        let world = app.world();
+        assert!(
+            world.get_entity(brick).is_err(),
+            "the ball missed the brick"
+        );
+        assert!(world.get::<Velocity>(ball).unwrap().y < 0.0);
+        assert!(world.get::<Transform>(ball).unwrap().translation.y < start.y);
+    }
+
+    #[test]
+    fn broken_bricks_are_out_of_the_way() {
+        let mut app = new_game();
+        despawn_all::<Brick>(&mut app);
+        despawn_all::<Ball>(&mut app);
+
+        let world = app.world_mut();
+        world.spawn(brick_bundle(Vec2::ZERO, BrickType::Normal, 1));
+        let behind = world
+            .spawn(brick_bundle(
+                Vec2::Y * (BRICK_SIZE.y + GAP_BETWEEN_BRICKS),
+                BrickType::Tough,
+                3,
+            ))
+            .id();
+
+        // Both balls reach the front brick in the same tick, the second after it broke
+        let start = Vec2::new(0.0, -BRICK_SIZE.y / 2.0 - BALL_DIAMETER / 2.0 - 10.0);
+        spawn_ball(&mut app, start, Vec2::Y * 6000.0);
+        spawn_ball(&mut app, start, Vec2::Y * 6000.0);
+        step(&mut app, 1);
+
+        assert_eq!(app.world().get::<Brick>(behind).unwrap().hit_points, 2);
+    }
+
+    #[test]
+    fn speed_bricks_only_speed_up_their_ball() {
+        let mut app = new_game();
+        let config = app.world().resource::<BreakoutConfig>().clone();
+
+        let (hitting, other) = break_speed_brick(&mut app, BALL_SPEED);
+
+        assert_eq!(hitting, BALL_SPEED * config.ball_speed_multiplier);
+        assert_eq!(other, BALL_SPEED);
+    }
+
+    #[test]
+    fn ball_speed_is_capped() {
+        let mut app = new_game();
+        let max_speed = app.world().resource::<BreakoutConfig>().ball_max_speed;
+
+        let (hitting, _) = break_speed_brick(&mut app, max_speed);
+
+        assert_eq!(hitting, max_speed);
+    }
+
+    #[test]
+    fn bullets_keep_their_own_speed() {
+        let mut app = new_game();
+        let bullet_speed = app.world().resource::<BreakoutConfig>().bullet_speed;
+        break_speed_brick(&mut app, BALL_SPEED);
+
+        catch_capsule(&mut app, PowerUp::LaserPaddle);
+        press(&mut app, KeyCode::Space);
+        step(&mut app, 2);
+
+        let world = app.world_mut();
+        let mut bullet_query = world.query_filtered::<&Velocity, With<Bullet>>();
+        assert!(bullet_query.iter(world).next().is_some());
+        for velocity in bullet_query.iter(world) {
+            assert!((velocity.length() - bullet_speed).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn broken_bricks_can_drop_capsules() {
+        let mut app = new_game();
+        despawn_all::<Brick>(&mut app);
+        app.world_mut()
+            .resource_mut::<BreakoutConfig>()
+            .capsule_drop_chance = 1.0;
+
+        break_brick(&mut app, Vec2::ZERO, BrickType::Normal);
+
+        assert_eq!(count::<Capsule>(&mut app), 1);
+    }
+
+    #[test]
+    fn wide_paddle_wears_off() {
+        let mut app = new_game();
+        let config = app.world().resource::<BreakoutConfig>().clone();
+        app.world_mut()
+            .resource_mut::<BreakoutConfig>()
+            .power_up_duration = 0.25;
+
+        catch_capsule(&mut app, PowerUp::WidePaddle);
+        assert_eq!(
+            paddle(&mut app).scale.x,
+            PADDLE_SIZE.x * config.wide_paddle_multiplier
+        );
+
+        step(&mut app, 30);
+        assert_eq!(paddle(&mut app).scale.x, PADDLE_SIZE.x);
+        assert_eq!(app.world().resource::<PowerUps>().describe(), "-");
+    }
+
+    #[test]
+    fn reloaded_sizes_and_colors_restyle_the_game() {
+        let mut app = new_game();
+        let mut config = app.world_mut().resource_mut::<BreakoutConfig>();
+        config.paddle_size = PADDLE_SIZE * 2.0;
+        config.ball_diameter = BALL_DIAMETER * 2.0;
+        config.brick_size = BRICK_SIZE / 2.0;
+        config.wall_color = Color::BLACK;
+        config.text_color = Color::WHITE;
+        step(&mut app, 1);
+
+        assert_eq!(paddle(&mut app).scale, (PADDLE_SIZE * 2.0).extend(1.0));
+        let world = app.world_mut();
+        let ball_transform = world
+            .query_filtered::<&Transform, With<Ball>>()
+            .single(world)
+            .unwrap();
+        assert_eq!(ball_transform.scale.x, BALL_DIAMETER * 2.0);
+
+        // The bricks are laid out again, still right under the ceiling
+        let brick_transforms = world
+            .query_filtered::<&Transform, With<GridCell>>()
+            .iter(world)
+            .collect::<Vec<_>>();
+        assert!(
+            brick_transforms
+                .iter()
+                .all(|transform| transform.scale.truncate() == BRICK_SIZE / 2.0)
+        );
+        let top_edge = brick_transforms
+            .iter()
+            .map(|transform| transform.translation.y + BRICK_SIZE.y / 4.0)
+            .fold(f32::MIN, f32::max);
+        assert!((top_edge - (TOP_WALL - GAP_BETWEEN_BRICKS_AND_CEILING)).abs() < 0.001);
+
+        assert!(
+            world
+                .query_filtered::<&Sprite, With<Wall>>()
+                .iter(world)
+                .all(|sprite| sprite.color == Color::BLACK)
+        );
+        let text_colors = world.query::<&TextColor>().iter(world).collect::<Vec<_>>();
+        assert!(text_colors.iter().any(|color| color.0 == Color::WHITE));
+        assert!(text_colors.iter().all(|color| color.0 != TEXT_COLOR));
+    }
+
+    #[test]
+    fn sticky_paddle_holds_the_ball_until_launched() {
+        let mut app = new_game();
+        despawn_all::<Ball>(&mut app);
+        catch_capsule(&mut app, PowerUp::StickyPaddle);
+
+        let paddle_top = BOTTOM_WALL + GAP_BETWEEN_PADDLE_AND_FLOOR + PADDLE_SIZE.y / 2.0;
+        spawn_ball(
+            &mut app,
+            Vec2::new(PADDLE_SIZE.x / 4.0, paddle_top + BALL_DIAMETER),
+            Vec2::NEG_Y * BALL_SPEED,
+        );
+        let stuck = step_until(&mut app, 60, |world| {
+            world.query::<&Served>().iter(world).next().is_some()
+        });
+        assert!(stuck, "the ball never stuck");
+
+        press(&mut app, KeyCode::Enter);
+        app.update();
+
+        let world = app.world_mut();
+        let velocity = world
+            .query_filtered::<&Velocity, With<Ball>>()
+            .single(world)
+            .unwrap();
+        assert!(velocity.x > 0.0 && velocity.y > 0.0);
+    }
+
+    #[test]
+    fn multi_ball_splits_the_balls_in_play() {
+        let mut app = new_game();
+        despawn_all::<Ball>(&mut app);
+        spawn_ball(&mut app, Vec2::ZERO, Vec2::Y * BALL_SPEED);
+
+        catch_capsule(&mut app, PowerUp::MultiBall);
+
+        assert_eq!(count::<Ball>(&mut app), 3);
+    }
+
+    #[test]
+    fn only_the_laser_paddle_fires() {
+        let mut app = new_game();
+
+        press(&mut app, KeyCode::Space);
+        step(&mut app, 10);
+        assert_eq!(count::<Bullet>(&mut app), 0);
+
+        catch_capsule(&mut app, PowerUp::LaserPaddle);
+        step(&mut app, 2);
+        assert!(count::<Bullet>(&mut app) >= 2);
+    }
+
+    #[test]
+    fn indestructible_bricks_dont_need_clearing() {
+        let mut app = new_game();
+        despawn_all::<Brick>(&mut app);
+
+        app.world_mut()
+            .spawn(brick_bundle(Vec2::ZERO, BrickType::Indestructible, 1));
+        step(&mut app, 2);
+
+        assert_eq!(state(&app), GameState::LevelCleared);
+    }
+
+    #[test]
+    fn clearing_the_bricks_moves_on_to_the_next_level() {
+        let mut app = new_game();
+
+        despawn_all::<Brick>(&mut app);
+        step(&mut app, 2);
+        assert_eq!(state(&app), GameState::LevelCleared);
+
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Playing);
+        step(&mut app, 2);
+
+        assert_eq!(state(&app), GameState::Playing);
+        assert_eq!(app.world().resource::<Levels>().current, 1);
+        assert!(count::<Brick>(&mut app) > 0);
+        assert_eq!(count::<Served>(&mut app), 1);
+    }
+
+    #[test]
+    fn clearing_the_last_level_wins() {
+        let mut app = new_game();
+        let mut levels = app.world_mut().resource_mut::<Levels>();
+        levels.current = levels.a.len() - 1;
+
+        despawn_all::<Brick>(&mut app);
+        step(&mut app, 2);
+
+        assert_eq!(state(&app), GameState::Victory);
+    }
+
+    #[test]
+    fn settings_rebind_the_paddle_keys_and_go_back_to_their_screen() {
+        let mut app = new_game();
+        app.world_mut().resource_mut::<InputBindingsFile>().a =
+            std::env::temp_dir().join("breakout_input_test.ron");
+        despawn_all::<Brick>(&mut app);
+        step(&mut app, 2);
+
+        tap(&mut app, KeyCode::Tab);
+        assert_eq!(state(&app), GameState::Settings);
+
+        // The paddle keys are the first line
+        tap(&mut app, KeyCode::Enter);
+        for key in [KeyCode::KeyI, KeyCode::KeyK, KeyCode::KeyJ, KeyCode::KeyL] {
+            tap(&mut app, key);
+        }
+        assert_eq!(
+            app.world().resource::<InputBindings>().thrust,
+            vec![
+                AxisBinding::Keys {
+                    up: KeyCode::KeyI,
+                    down: KeyCode::KeyK,
+                    left: KeyCode::KeyJ,
+                    right: KeyCode::KeyL,
+                },
+                InputBindings::default().thrust[1],
+            ]
+        );
+
+        tap(&mut app, KeyCode::Escape);
+        assert_eq!(state(&app), GameState::LevelCleared);
+        assert_eq!(app.world().resource::<Levels>().current, 1);
+    }
+
+    #[test]
+    fn shipped_config_matches_the_defaults() {
+        let text = include_str!("../assets/config/breakout.ron");
+
+        assert_eq!(
+            parse_config::<BreakoutConfig>(text.as_bytes()),
+            Ok(BreakoutConfig::default())
+        );
+    }
+
+    #[test]
+    fn bundled_levels_load() {
+        let app = new_game();
+
+        assert_eq!(app.world().resource::<Levels>().a.len(), LEVEL_PATHS.len());
+    }
+
+    #[test]
+    fn level_cells_are_laid_out_under_the_ceiling() {
+        let level = parse_level(
+            br#"(
+                name: "Test",
+                arena: (left: -400.0, right: 400.0),
+                bricks: ["N . S2", "? E N"],
+            )"#,
+        )
+        .unwrap();
+
+        assert_eq!(level.arena.width(), 800.0);
+        assert_eq!(level.bricks.len(), 5);
+        assert_eq!(level.bricks[0].r#type, Some(BrickType::Normal));
+        assert_eq!(level.bricks[1].r#type, Some(BrickType::Speed));
+        assert_eq!(level.bricks[1].hit_points, Some(2));
+        assert_eq!(level.bricks[0].hit_points, None);
+        assert_eq!(level.bricks[2].r#type, None);
+
+        // Centered, top row first
+        let position = |brick: usize| {
+            level
+                .grid
+                .position(&level.arena, level.bricks[brick].cell, BRICK_SIZE)
+        };
+        assert_eq!(position(0).x, -position(1).x);
+        assert!(position(2).y < position(0).y);
+        assert_eq!(
+            position(0).y + BRICK_SIZE.y / 2.0,
+            TOP_WALL - GAP_BETWEEN_BRICKS_AND_CEILING
+        );
+    }
+
+    #[test]
+    fn broken_levels_report_every_problem() {
+        let error = parse_level(
+            br#"(
+                name: "Broken",
+                bricks: ["N Z N0", "N N"],
+            )"#,
+        )
+        .unwrap_err();
+        assert_eq!(error.problems.len(), 2);
+
+        let error = parse_level(br#"(name: "Uneven", bricks: ["N N", "N"])"#).unwrap_err();
+        assert_eq!(error.problems.len(), 1);
+
+        let too_wide = format!(r#"(name: "Wide", bricks: ["{}"])"#, "N ".repeat(20));
+        assert!(parse_level(too_wide.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn generated_level_fills_the_arena() {
+        let level = Level::generated(BRICK_SIZE);
+
+        assert!(!level.bricks.is_empty());
+        assert!(level.grid.check_fit(&level.arena, BRICK_SIZE).is_ok());
+        assert!(level.bricks.iter().all(|brick| brick.r#type.is_none()));
+    }
 }
@@ -0,0 +1,806 @@
+// Collision between simple shapes, without a physics engine.
+//
+// An entity's `Shape` and `Transform` give its `BoundingVolume` in the world.
+// `BoundsPlugin` keeps that volume in the `Bounds` component of every shaped entity,
+// so gameplay systems can test it against the volumes of other entities.
+
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::math::bounding::BoundingVolume as _;
+use bevy::math::bounding::{Aabb2d, BoundingCircle, IntersectsVolume};
+use bevy::prelude::*;
+use bevy_rapier2d::geometry::Collider;
+
+// Below this, a sine or cosine is considered to be zero when deciding if a rectangle is axis aligned
+const AXIS_ALIGNED_EPSILON: f32 = 1e-4;
+
+#[derive(Component)]
+pub enum Shape {
+    Circle(Circle),
+    Rectangle(Rectangle),
+    Triangle(Triangle2d),
+    Annulus(Annulus),
+}
+
+impl Shape {
+    /// Physics collider matching the shape, so what is drawn is what collides.
+    /// Annuli are filled, as nothing can fit through the hole of a bullet.
+    pub fn collider(&self) -> Collider {
+        match self {
+            Shape::Circle(circle) => Collider::ball(circle.radius),
+            Shape::Rectangle(rectangle) => {
+                Collider::cuboid(rectangle.half_size.x, rectangle.half_size.y)
+            }
+            Shape::Triangle(triangle) => {
+                let [a, b, c] = triangle.vertices;
+                Collider::triangle(a, b, c)
+            }
+            Shape::Annulus(annulus) => Collider::ball(annulus.outer_circle.radius),
+        }
+    }
+}
+
+/// Rectangle rotated around its center.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Obb2d {
+    pub center: Vec2,
+    pub half_size: Vec2,
+    pub rotation: Rot2,
+}
+
+/// Ring between two concentric circles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingAnnulus {
+    pub center: Vec2,
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+}
+
+impl BoundingAnnulus {
+    pub fn inner_circle(&self) -> BoundingCircle {
+        BoundingCircle::new(self.center, self.inner_radius)
+    }
+
+    pub fn outer_circle(&self) -> BoundingCircle {
+        BoundingCircle::new(self.center, self.outer_radius)
+    }
+}
+
+/// World space volume of an entity, built from its [`Shape`] and [`Transform`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum BoundingVolume {
+    Circle(BoundingCircle),
+    Aabb(Aabb2d),
+    Obb(Obb2d),
+    Triangle([Vec2; 3]),
+    Annulus(BoundingAnnulus),
+}
+
+impl BoundingVolume {
+    pub fn from_shape(shape: &Shape, transform: &Transform) -> Self {
+        let center = transform.translation.truncate();
+        let scale = transform.scale.truncate();
+        let rotation = Rot2::radians(transform.rotation.to_euler(EulerRot::ZYX).0);
+
+        match shape {
+            Shape::Circle(circle) => BoundingVolume::Circle(BoundingCircle::new(
+                center,
+                circle.radius * scale.max_element(),
+            )),
+            Shape::Rectangle(rectangle) => {
+                let half_size = rectangle.half_size * scale;
+
+                // Rectangles rotated by a multiple of 90 degrees are still axis aligned
+                if rotation.sin.abs() < AXIS_ALIGNED_EPSILON {
+                    BoundingVolume::Aabb(Aabb2d::new(center, half_size))
+                } else if rotation.cos.abs() < AXIS_ALIGNED_EPSILON {
+                    BoundingVolume::Aabb(Aabb2d::new(center, half_size.yx()))
+                } else {
+                    BoundingVolume::Obb(Obb2d {
+                        center,
+                        half_size,
+                        rotation,
+                    })
+                }
+            }
+            Shape::Triangle(triangle) => BoundingVolume::Triangle(
+                triangle
+                    .vertices
+                    .map(|vertex| center + rotation * (vertex * scale)),
+            ),
+            Shape::Annulus(annulus) => BoundingVolume::Annulus(BoundingAnnulus {
+                center,
+                inner_radius: annulus.inner_circle.radius * scale.max_element(),
+                outer_radius: annulus.outer_circle.radius * scale.max_element(),
+            }),
+        }
+    }
+
+    pub fn intersects(&self, other: &BoundingVolume) -> bool {
+        match (self, other) {
+            // Both volumes are connected, so touching the outer circle without fitting
+            // entirely inside the hole means touching the ring itself
+            (BoundingVolume::Annulus(annulus), other)
+            | (other, BoundingVolume::Annulus(annulus)) => {
+                other.intersects_circle(&annulus.outer_circle())
+                    && !other.is_inside_circle(&annulus.inner_circle())
+            }
+            (BoundingVolume::Circle(circle), other) | (other, BoundingVolume::Circle(circle)) => {
+                other.intersects_circle(circle)
+            }
+            (BoundingVolume::Aabb(a), BoundingVolume::Aabb(b)) => a.intersects(b),
+            (a, b) => polygons_intersect(&a.vertices(), &b.vertices()),
+        }
+    }
+
+    /// Smallest axis aligned box containing the volume.
+    pub fn aabb(&self) -> Aabb2d {
+        match self {
+            BoundingVolume::Circle(circle) => circle.aabb_2d(),
+            BoundingVolume::Aabb(aabb) => *aabb,
+            BoundingVolume::Annulus(annulus) => annulus.outer_circle().aabb_2d(),
+            BoundingVolume::Obb(_) | BoundingVolume::Triangle(_) => {
+                Aabb2d::from_point_cloud(Isometry2d::IDENTITY, &self.vertices())
+            }
+        }
+    }
+
+    /// Smallest circle containing the volume, exact for circles and annuli.
+    pub fn bounding_circle(&self) -> BoundingCircle {
+        match self {
+            BoundingVolume::Circle(circle) => *circle,
+            BoundingVolume::Annulus(annulus) => annulus.outer_circle(),
+            BoundingVolume::Aabb(_) | BoundingVolume::Obb(_) | BoundingVolume::Triangle(_) => {
+                let aabb = self.aabb();
+                BoundingCircle::new(aabb.center(), aabb.half_size().length())
+            }
+        }
+    }
+
+    // Corners of polygonal volumes, empty for round ones
+    fn vertices(&self) -> Vec<Vec2> {
+        match self {
+            BoundingVolume::Aabb(aabb) => Vec::from([
+                aabb.min,
+                Vec2::new(aabb.max.x, aabb.min.y),
+                aabb.max,
+                Vec2::new(aabb.min.x, aabb.max.y),
+            ]),
+            BoundingVolume::Obb(obb) => [
+                Vec2::new(-1.0, -1.0),
+                Vec2::new(1.0, -1.0),
+                Vec2::new(1.0, 1.0),
+                Vec2::new(-1.0, 1.0),
+            ]
+            .iter()
+            .map(|corner| obb.center + obb.rotation * (*corner * obb.half_size))
+            .collect(),
+            BoundingVolume::Triangle(vertices) => Vec::from(vertices),
+            BoundingVolume::Circle(_) | BoundingVolume::Annulus(_) => Vec::new(),
+        }
+    }
+
+    fn intersects_circle(&self, circle: &BoundingCircle) -> bool {
+        match self {
+            BoundingVolume::Circle(other) => circle.intersects(other),
+            BoundingVolume::Aabb(aabb) => circle.intersects(aabb),
+            BoundingVolume::Annulus(annulus) => {
+                circle.intersects(&annulus.outer_circle())
+                    && !circle_is_inside_circle(circle, &annulus.inner_circle())
+            }
+            BoundingVolume::Obb(_) | BoundingVolume::Triangle(_) => {
+                circle_intersects_polygon(circle, &self.vertices())
+            }
+        }
+    }
+
+    fn is_inside_circle(&self, circle: &BoundingCircle) -> bool {
+        match self {
+            BoundingVolume::Circle(other) => circle_is_inside_circle(other, circle),
+            BoundingVolume::Annulus(annulus) => {
+                circle_is_inside_circle(&annulus.outer_circle(), circle)
+            }
+            BoundingVolume::Aabb(_) | BoundingVolume::Obb(_) | BoundingVolume::Triangle(_) => self
+                .vertices()
+                .iter()
+                .all(|vertex| vertex.distance_squared(circle.center) <= circle.radius().powi(2)),
+        }
+    }
+}
+
+fn circle_is_inside_circle(inner: &BoundingCircle, outer: &BoundingCircle) -> bool {
+    inner.center.distance(outer.center) + inner.radius() <= outer.radius()
+}
+
+// Works for convex polygons with any winding
+fn polygon_contains_point(polygon: &[Vec2], point: Vec2) -> bool {
+    let mut sign = 0.0;
+
+    for (i, start) in polygon.iter().enumerate() {
+        let end = polygon[(i + 1) % polygon.len()];
+        let cross = (end - *start).perp_dot(point - *start);
+
+        if cross != 0.0 {
+            if sign * cross < 0.0 {
+                return false;
+            }
+            sign = cross;
+        }
+    }
+
+    true
+}
+
+fn closest_point_on_segment(start: Vec2, end: Vec2, point: Vec2) -> Vec2 {
+    let segment = end - start;
+    let t = (point - start).dot(segment) / segment.length_squared().max(f32::EPSILON);
+    start + segment * t.clamp(0.0, 1.0)
+}
+
+fn circle_intersects_polygon(circle: &BoundingCircle, polygon: &[Vec2]) -> bool {
+    if polygon_contains_point(polygon, circle.center) {
+        return true;
+    }
+
+    polygon.iter().enumerate().any(|(i, start)| {
+        let end = polygon[(i + 1) % polygon.len()];
+        closest_point_on_segment(*start, end, circle.center).distance_squared(circle.center)
+            <= circle.radius().powi(2)
+    })
+}
+
+// Separating axis theorem: two convex polygons are disjoint if and only if
+// their projections don't overlap on the normal of one of their edges
+fn polygons_intersect(a: &[Vec2], b: &[Vec2]) -> bool {
+    let project = |polygon: &[Vec2], axis: Vec2| {
+        polygon
+            .iter()
+            .map(|vertex| vertex.dot(axis))
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), p| {
+                (min.min(p), max.max(p))
+            })
+    };
+
+    [a, b].iter().all(|polygon| {
+        polygon.iter().enumerate().all(|(i, start)| {
+            let axis = (polygon[(i + 1) % polygon.len()] - *start).perp();
+            let (a_min, a_max) = project(a, axis);
+            let (b_min, b_max) = project(b, axis);
+            a_min <= b_max && b_min <= a_max
+        })
+    })
+}
+
+/// Side of a box that something hit.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Collision {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl Collision {
+    /// Direction out of the box through the side.
+    pub fn normal(&self) -> Vec2 {
+        match self {
+            Collision::Left => Vec2::NEG_X,
+            Collision::Right => Vec2::X,
+            Collision::Top => Vec2::Y,
+            Collision::Bottom => Vec2::NEG_Y,
+        }
+    }
+}
+
+// Returns `Some` if `circular_object` collides with `bounding_box`.
+// The returned `Collision` is the side of `bounding_box` that `circular_object` hit.
+pub fn collision(circular_object: BoundingCircle, bounding_box: Aabb2d) -> Option<Collision> {
+    if !circular_object.intersects(&bounding_box) {
+        return None;
+    }
+
+    let closest = bounding_box.closest_point(circular_object.center());
+    let offset = circular_object.center() - closest;
+    let side = if offset.x.abs() > offset.y.abs() {
+        if offset.x < 0. {
+            Collision::Left
+        } else {
+            Collision::Right
+        }
+    } else if offset.y > 0. {
+        Collision::Top
+    } else {
+        Collision::Bottom
+    };
+
+    Some(side)
+}
+
+// Returns `Some` if `circular_object` touches `bounding_box` on its way along `motion`, moving into it.
+// The returned time is the fraction of `motion` covered before they first touch, and the
+// returned `Collision` is the side of `bounding_box` that is touched. A circle already touching
+// the box is hit at time zero, unless it moves away from the side it touches.
+pub fn sweep(
+    circular_object: BoundingCircle,
+    motion: Vec2,
+    bounding_box: Aabb2d,
+) -> Option<(f32, Collision)> {
+    let center = circular_object.center();
+    let radius = circular_object.radius();
+
+    if let Some(side) = collision(circular_object, bounding_box) {
+        return (motion.dot(side.normal()) < 0.0).then_some((0.0, side));
+    }
+
+    // The center of the circle touches the box when it enters the box grown by the radius,
+    // a box with rounded corners, which is first crossed through the square box around it
+    let min = bounding_box.min - radius;
+    let max = bounding_box.max + radius;
+    let mut enter = [0.0; 2];
+    let mut exit = [0.0; 2];
+    for axis in 0..2 {
+        if motion[axis] == 0.0 {
+            if center[axis] < min[axis] || center[axis] > max[axis] {
+                return None;
+            }
+            enter[axis] = f32::NEG_INFINITY;
+            exit[axis] = f32::INFINITY;
+        } else {
+            let near = if motion[axis] > 0.0 {
+                min[axis]
+            } else {
+                max[axis]
+            };
+            let far = if motion[axis] > 0.0 {
+                max[axis]
+            } else {
+                min[axis]
+            };
+            enter[axis] = (near - center[axis]) / motion[axis];
+            exit[axis] = (far - center[axis]) / motion[axis];
+        }
+    }
+
+    let time = enter[0].max(enter[1]);
+    if time > exit[0].min(exit[1]) || time > 1.0 || exit[0].min(exit[1]) < 0.0 {
+        return None;
+    }
+
+    // Between the corners, the square box is the rounded one
+    let point = center + motion * time.max(0.0);
+    let corner = point.clamp(bounding_box.min, bounding_box.max);
+    if corner.x == point.x || corner.y == point.y {
+        let side = if enter[0] > enter[1] {
+            if motion.x > 0.0 {
+                Collision::Left
+            } else {
+                Collision::Right
+            }
+        } else if motion.y > 0.0 {
+            Collision::Bottom
+        } else {
+            Collision::Top
+        };
+        return Some((time.max(0.0), side));
+    }
+
+    // At a corner, the circle touches the box once its center is a radius away from the corner
+    let offset = center - corner;
+    let a = motion.length_squared();
+    let b = 2.0 * offset.dot(motion);
+    let c = offset.length_squared() - radius * radius;
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let time = (-b - discriminant.sqrt()) / (2.0 * a);
+    if !(0.0..=1.0).contains(&time) {
+        return None;
+    }
+
+    // The corner is on two sides, and the circle touches the one it moves into the most
+    let offset = center + motion * time - corner;
+    let horizontal = if offset.x < 0.0 {
+        Collision::Left
+    } else {
+        Collision::Right
+    };
+    let vertical = if offset.y > 0.0 {
+        Collision::Top
+    } else {
+        Collision::Bottom
+    };
+    let into_horizontal = motion.dot(horizontal.normal()) < 0.0;
+    let into_vertical = motion.dot(vertical.normal()) < 0.0;
+    let side = if into_horizontal && (!into_vertical || offset.x.abs() > offset.y.abs()) {
+        horizontal
+    } else {
+        vertical
+    };
+
+    Some((time, side))
+}
+
+/// World space volume of a shaped entity, kept up to date by [`BoundsPlugin`].
+#[derive(Component, Debug, Clone, PartialEq, Deref)]
+pub struct Bounds {
+    pub a: BoundingVolume,
+}
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UpdateBounds;
+
+pub fn update_bounds(
+    mut commands: Commands,
+    mut query: Query<
+        (Entity, &Shape, &Transform, Option<&mut Bounds>),
+        Or<(Changed<Shape>, Changed<Transform>)>,
+    >,
+) {
+    for (entity, shape, transform, maybe_bounds) in &mut query {
+        let volume = BoundingVolume::from_shape(shape, transform);
+
+        match maybe_bounds {
+            Some(mut bounds) => bounds.a = volume,
+            None => {
+                commands.entity(entity).insert(Bounds { a: volume });
+            }
+        }
+    }
+}
+
+// Gives every entity with a `Shape` its `Bounds`, updated in `schedule` in the `UpdateBounds` set.
+// Systems testing bounds should run after that set, and after whatever moves the entities.
+pub struct BoundsPlugin {
+    pub schedule: InternedScheduleLabel,
+}
+
+impl BoundsPlugin {
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        BoundsPlugin {
+            schedule: schedule.intern(),
+        }
+    }
+}
+
+impl Plugin for BoundsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(self.schedule, update_bounds.in_set(UpdateBounds));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+
+    fn circle(center: Vec2, radius: f32) -> BoundingVolume {
+        BoundingVolume::Circle(BoundingCircle::new(center, radius))
+    }
+
+    fn aabb(center: Vec2, half_size: Vec2) -> BoundingVolume {
+        BoundingVolume::Aabb(Aabb2d::new(center, half_size))
+    }
+
+    // A 2x2 square rotated 45 degrees, its corners are sqrt(2) away from the center
+    fn diamond(center: Vec2) -> BoundingVolume {
+        BoundingVolume::Obb(Obb2d {
+            center,
+            half_size: Vec2::ONE,
+            rotation: Rot2::radians(FRAC_PI_4),
+        })
+    }
+
+    fn triangle(offset: Vec2) -> BoundingVolume {
+        BoundingVolume::Triangle([
+            offset + Vec2::new(0.0, 0.0),
+            offset + Vec2::new(2.0, 0.0),
+            offset + Vec2::new(0.0, 2.0),
+        ])
+    }
+
+    fn annulus(center: Vec2, inner_radius: f32, outer_radius: f32) -> BoundingVolume {
+        BoundingVolume::Annulus(BoundingAnnulus {
+            center,
+            inner_radius,
+            outer_radius,
+        })
+    }
+
+    // Checks both argument orders, as every pair must be symmetric
+    fn assert_intersects(a: &BoundingVolume, b: &BoundingVolume, expected: bool) {
+        assert_eq!(a.intersects(b), expected, "{a:?} vs {b:?}");
+        assert_eq!(b.intersects(a), expected, "{b:?} vs {a:?}");
+    }
+
+    #[test]
+    fn from_shape_picks_aabb_for_unrotated_and_quarter_turned_rectangles() {
+        let shape = Shape::Rectangle(Rectangle::new(4.0, 2.0));
+
+        let volume = BoundingVolume::from_shape(&shape, &Transform::from_xyz(1.0, 2.0, 0.0));
+        assert_eq!(volume, aabb(Vec2::new(1.0, 2.0), Vec2::new(2.0, 1.0)));
+
+        let transform = Transform::from_rotation(Quat::from_rotation_z(FRAC_PI_2));
+        let volume = BoundingVolume::from_shape(&shape, &transform);
+        let BoundingVolume::Aabb(rotated) = volume else {
+            panic!("expected an aabb, got {volume:?}");
+        };
+        assert!(rotated.half_size().abs_diff_eq(Vec2::new(1.0, 2.0), 1e-5));
+    }
+
+    #[test]
+    fn from_shape_picks_obb_for_rotated_rectangles() {
+        let shape = Shape::Rectangle(Rectangle::new(2.0, 2.0));
+        let transform = Transform::from_rotation(Quat::from_rotation_z(FRAC_PI_4));
+
+        let BoundingVolume::Obb(obb) = BoundingVolume::from_shape(&shape, &transform) else {
+            panic!("expected an obb");
+        };
+        assert!((obb.rotation.as_radians() - FRAC_PI_4).abs() < 1e-5);
+    }
+
+    #[test]
+    fn from_shape_rotates_triangles() {
+        let shape = Shape::Triangle(Triangle2d::new(Vec2::X, Vec2::Y, Vec2::NEG_X));
+        let transform =
+            Transform::from_xyz(10.0, 0.0, 0.0).with_rotation(Quat::from_rotation_z(FRAC_PI_2));
+
+        let BoundingVolume::Triangle(vertices) = BoundingVolume::from_shape(&shape, &transform)
+        else {
+            panic!("expected a triangle");
+        };
+        assert!(vertices[0].abs_diff_eq(Vec2::new(10.0, 1.0), 1e-5));
+        assert!(vertices[1].abs_diff_eq(Vec2::new(9.0, 0.0), 1e-5));
+        assert!(vertices[2].abs_diff_eq(Vec2::new(10.0, -1.0), 1e-5));
+    }
+
+    #[test]
+    fn aabb_contains_the_whole_volume() {
+        let obb = diamond(Vec2::ZERO).aabb();
+        assert!(
+            obb.half_size()
+                .abs_diff_eq(Vec2::splat(2.0_f32.sqrt()), 1e-5)
+        );
+
+        let ring = annulus(Vec2::new(1.0, 1.0), 1.0, 3.0).aabb();
+        assert_eq!(ring.min, Vec2::new(-2.0, -2.0));
+        assert_eq!(ring.max, Vec2::new(4.0, 4.0));
+    }
+
+    #[test]
+    fn circle_circle() {
+        assert_intersects(
+            &circle(Vec2::ZERO, 1.0),
+            &circle(Vec2::new(1.9, 0.0), 1.0),
+            true,
+        );
+        assert_intersects(
+            &circle(Vec2::ZERO, 1.0),
+            &circle(Vec2::new(2.1, 0.0), 1.0),
+            false,
+        );
+    }
+
+    #[test]
+    fn circle_aabb() {
+        let rectangle = aabb(Vec2::ZERO, Vec2::new(2.0, 1.0));
+        assert_intersects(&circle(Vec2::new(2.9, 0.0), 1.0), &rectangle, true);
+        assert_intersects(&circle(Vec2::new(2.8, 1.8), 1.0), &rectangle, false);
+    }
+
+    #[test]
+    fn circle_obb() {
+        // The corner of the diamond reaches x = sqrt(2)
+        assert_intersects(
+            &circle(Vec2::new(2.3, 0.0), 1.0),
+            &diamond(Vec2::ZERO),
+            true,
+        );
+        // This circle would touch the unrotated square, but not the diamond
+        assert_intersects(
+            &circle(Vec2::new(1.3, 1.3), 0.5),
+            &diamond(Vec2::ZERO),
+            false,
+        );
+    }
+
+    #[test]
+    fn circle_triangle() {
+        assert_intersects(
+            &circle(Vec2::new(0.5, 0.5), 0.1),
+            &triangle(Vec2::ZERO),
+            true,
+        );
+        assert_intersects(
+            &circle(Vec2::new(1.2, 1.2), 0.5),
+            &triangle(Vec2::ZERO),
+            true,
+        );
+        assert_intersects(
+            &circle(Vec2::new(2.0, 2.0), 0.5),
+            &triangle(Vec2::ZERO),
+            false,
+        );
+    }
+
+    #[test]
+    fn circle_annulus() {
+        let ring = annulus(Vec2::ZERO, 2.0, 3.0);
+        assert_intersects(&circle(Vec2::new(2.5, 0.0), 0.1), &ring, true);
+        assert_intersects(&circle(Vec2::ZERO, 2.5), &ring, true);
+        // Inside the hole
+        assert_intersects(&circle(Vec2::ZERO, 1.0), &ring, false);
+        // Outside the ring
+        assert_intersects(&circle(Vec2::new(5.0, 0.0), 1.0), &ring, false);
+    }
+
+    #[test]
+    fn aabb_aabb() {
+        let rectangle = aabb(Vec2::ZERO, Vec2::ONE);
+        assert_intersects(&aabb(Vec2::new(1.5, 1.5), Vec2::ONE), &rectangle, true);
+        assert_intersects(&aabb(Vec2::new(2.5, 0.0), Vec2::ONE), &rectangle, false);
+    }
+
+    #[test]
+    fn aabb_obb() {
+        assert_intersects(
+            &aabb(Vec2::new(2.3, 0.0), Vec2::ONE),
+            &diamond(Vec2::ZERO),
+            true,
+        );
+        // Overlaps the diamond's bounding box, but not the diamond itself
+        assert_intersects(
+            &aabb(Vec2::new(2.0, 2.0), Vec2::ONE),
+            &diamond(Vec2::ZERO),
+            false,
+        );
+    }
+
+    #[test]
+    fn aabb_triangle() {
+        assert_intersects(
+            &aabb(Vec2::new(2.4, 0.5), Vec2::ONE),
+            &triangle(Vec2::ZERO),
+            true,
+        );
+        // Beyond the hypotenuse, but inside the triangle's bounding box
+        assert_intersects(
+            &aabb(Vec2::new(2.0, 2.0), Vec2::splat(0.4)),
+            &triangle(Vec2::ZERO),
+            false,
+        );
+    }
+
+    #[test]
+    fn aabb_annulus() {
+        let ring = annulus(Vec2::ZERO, 2.0, 3.0);
+        assert_intersects(&aabb(Vec2::new(2.5, 0.0), Vec2::splat(0.2)), &ring, true);
+        assert_intersects(&aabb(Vec2::ZERO, Vec2::ONE), &ring, false);
+        assert_intersects(&aabb(Vec2::new(5.0, 0.0), Vec2::ONE), &ring, false);
+    }
+
+    #[test]
+    fn obb_obb() {
+        assert_intersects(&diamond(Vec2::ZERO), &diamond(Vec2::new(2.7, 0.0)), true);
+        // The flat sides face each other
+        assert_intersects(&diamond(Vec2::ZERO), &diamond(Vec2::new(1.5, 1.5)), false);
+    }
+
+    #[test]
+    fn obb_triangle() {
+        assert_intersects(&diamond(Vec2::new(-1.0, 1.0)), &triangle(Vec2::ZERO), true);
+        assert_intersects(&diamond(Vec2::new(2.2, 2.2)), &triangle(Vec2::ZERO), false);
+    }
+
+    #[test]
+    fn obb_annulus() {
+        let ring = annulus(Vec2::ZERO, 2.0, 3.0);
+        assert_intersects(&diamond(Vec2::new(1.0, 0.0)), &ring, true);
+        // The diamond's corners are sqrt(2) away from the center, inside the hole
+        assert_intersects(&diamond(Vec2::ZERO), &ring, false);
+    }
+
+    #[test]
+    fn triangle_triangle() {
+        assert_intersects(&triangle(Vec2::ZERO), &triangle(Vec2::new(0.9, 0.9)), true);
+        assert_intersects(&triangle(Vec2::ZERO), &triangle(Vec2::new(1.1, 1.1)), false);
+    }
+
+    #[test]
+    fn triangle_annulus() {
+        assert_intersects(&triangle(Vec2::ZERO), &annulus(Vec2::ZERO, 1.0, 2.0), true);
+        assert_intersects(
+            &triangle(Vec2::new(0.1, 0.1)),
+            &annulus(Vec2::ZERO, 5.0, 6.0),
+            false,
+        );
+    }
+
+    #[test]
+    fn annulus_annulus() {
+        let ring = annulus(Vec2::ZERO, 2.0, 3.0);
+        assert_intersects(&annulus(Vec2::new(4.0, 0.0), 0.5, 1.5), &ring, true);
+        // A small ring inside the hole of the big one
+        assert_intersects(&annulus(Vec2::ZERO, 0.5, 1.0), &ring, false);
+        assert_intersects(&annulus(Vec2::new(10.0, 0.0), 0.5, 1.0), &ring, false);
+    }
+
+    #[test]
+    fn collision_reports_the_side_hit() {
+        let bounding_box = Aabb2d::new(Vec2::ZERO, Vec2::new(2.0, 1.0));
+        let hit = |center: Vec2| collision(BoundingCircle::new(center, 0.5), bounding_box);
+
+        assert_eq!(hit(Vec2::new(-2.3, 0.0)), Some(Collision::Left));
+        assert_eq!(hit(Vec2::new(2.3, 0.0)), Some(Collision::Right));
+        assert_eq!(hit(Vec2::new(0.0, 1.3)), Some(Collision::Top));
+        assert_eq!(hit(Vec2::new(0.0, -1.3)), Some(Collision::Bottom));
+        assert_eq!(hit(Vec2::new(0.0, 3.0)), None);
+    }
+
+    #[test]
+    fn sweep_finds_the_first_touch() {
+        let bounding_box = Aabb2d::new(Vec2::ZERO, Vec2::new(2.0, 1.0));
+        let circle = BoundingCircle::new(Vec2::new(-5.0, 0.0), 0.5);
+
+        let (time, side) = sweep(circle, Vec2::new(5.0, 0.0), bounding_box).unwrap();
+        assert!((time - 0.5).abs() < 1e-5);
+        assert_eq!(side, Collision::Left);
+
+        let circle = BoundingCircle::new(Vec2::new(0.0, 5.0), 0.5);
+        let (time, side) = sweep(circle, Vec2::new(0.0, -7.0), bounding_box).unwrap();
+        assert!((time - 0.5).abs() < 1e-5);
+        assert_eq!(side, Collision::Top);
+
+        // Stopping short, or going the other way
+        assert_eq!(sweep(circle, Vec2::new(0.0, -3.0), bounding_box), None);
+        assert_eq!(sweep(circle, Vec2::new(0.0, 7.0), bounding_box), None);
+    }
+
+    #[test]
+    fn sweep_catches_what_jumps_over_the_box() {
+        let bounding_box = Aabb2d::new(Vec2::ZERO, Vec2::new(2.0, 0.1));
+        let circle = BoundingCircle::new(Vec2::new(0.0, -3.0), 0.5);
+        let motion = Vec2::new(0.0, 6.0);
+
+        // Neither end touches the box
+        assert_eq!(collision(circle, bounding_box), None);
+        let end = BoundingCircle::new(circle.center() + motion, 0.5);
+        assert_eq!(collision(end, bounding_box), None);
+
+        let (time, side) = sweep(circle, motion, bounding_box).unwrap();
+        assert!((time - 2.4 / 6.0).abs() < 1e-5);
+        assert_eq!(side, Collision::Bottom);
+    }
+
+    #[test]
+    fn sweep_rounds_the_corners() {
+        let bounding_box = Aabb2d::new(Vec2::ZERO, Vec2::new(1.0, 1.0));
+
+        // Clips the square around the corner, but not the circle in it
+        let circle = BoundingCircle::new(Vec2::new(-0.1, 3.0), 0.5);
+        assert_eq!(sweep(circle, Vec2::new(4.0, -4.0), bounding_box), None);
+
+        // Straight at the corner, diagonally
+        let circle = BoundingCircle::new(Vec2::new(3.0, 3.0), 0.5);
+        let (time, side) = sweep(circle, Vec2::new(-4.0, -4.0), bounding_box).unwrap();
+        let expected = (2.0 * 2.0f32.sqrt() - 0.5) / (4.0 * 2.0f32.sqrt());
+        assert!((time - expected).abs() < 1e-5);
+        assert!(matches!(side, Collision::Right | Collision::Top));
+
+        // Grazing the corner on the way down, moving mostly into the top
+        let circle = BoundingCircle::new(Vec2::new(1.3, 3.0), 0.5);
+        let (_, side) = sweep(circle, Vec2::new(0.2, -4.0), bounding_box).unwrap();
+        assert_eq!(side, Collision::Top);
+    }
+
+    #[test]
+    fn sweep_lets_touching_circles_move_away() {
+        let bounding_box = Aabb2d::new(Vec2::ZERO, Vec2::new(1.0, 1.0));
+        let circle = BoundingCircle::new(Vec2::new(0.0, 1.5), 0.5);
+
+        assert_eq!(sweep(circle, Vec2::new(1.0, 1.0), bounding_box), None);
+        assert_eq!(
+            sweep(circle, Vec2::new(1.0, -1.0), bounding_box),
+            Some((0.0, Collision::Top))
+        );
+    }
+}
@@ -0,0 +1,49 @@
+// A sound played whenever something collides.
+//
+// Gameplay systems write a `BallCollisionEvent` for every hit, and the sound is played
+// at most once per frame, however many hits there were.
+
+use bevy::prelude::*;
+
+#[derive(Resource, Deref)]
+pub struct CollisionSound {
+    pub a: Handle<AudioSource>,
+}
+
+#[derive(Event, Default)]
+pub struct BallCollisionEvent;
+
+pub fn play_collision_sound(
+    mut commands: Commands,
+    mut collision_events: EventReader<BallCollisionEvent>,
+    sound: Res<CollisionSound>,
+) {
+    // Play a sound once per frame if a collision occurred.
+    if !collision_events.is_empty() {
+        // This prevents events staying active on the next frame.
+        collision_events.clear();
+        commands.spawn((AudioPlayer(sound.clone()), PlaybackSettings::DESPAWN));
+    }
+}
+
+// Loads the sound at `path`, inside the assets folder, and plays it after the frame's hits.
+// Hits written in `FixedUpdate` or `Update` are heard the same frame.
+pub struct CollisionSoundPlugin {
+    pub path: &'static str,
+}
+
+impl Plugin for CollisionSoundPlugin {
+    fn build(&self, app: &mut App) {
+        let path = self.path;
+        app.add_event::<BallCollisionEvent>()
+            .add_systems(
+                Startup,
+                move |mut commands: Commands, asset_server: Res<AssetServer>| {
+                    commands.insert_resource(CollisionSound {
+                        a: asset_server.load(path),
+                    });
+                },
+            )
+            .add_systems(PostUpdate, play_collision_sound);
+    }
+}
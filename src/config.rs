@@ -0,0 +1,198 @@
+// Game tuning loaded from RON asset files shared by the games.
+//
+// Each game keeps its tuning in a config type that is both an asset and a resource.
+// The asset is loaded through the `AssetServer`, checked, and copied into the resource
+// that the gameplay systems read. With the `hot_reload` feature the file is watched,
+// so saving it while the game runs applies the new values straight away.
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext, LoadState};
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::marker::PhantomData;
+
+// A game's tuning. Fields missing from the file fall back on `Default`, which takes each field
+// from the game's constant of the same name. Those constants are then only defaults, and the
+// gameplay reads the config resource instead, so that reloading the file changes the game.
+pub trait Config: Asset + Resource + Clone + Default + DeserializeOwned {
+    fn validate(&self) -> Result<(), ConfigError>;
+}
+
+// Everything wrong with a config file, reported all at once
+#[derive(Debug, Default, PartialEq)]
+pub struct ConfigError {
+    pub problems: Vec<String>,
+}
+
+impl ConfigError {
+    pub fn check(&mut self, ok: bool, problem: impl Into<String>) {
+        if !ok {
+            self.problems.push(problem.into());
+        }
+    }
+
+    pub fn positive(&mut self, name: &str, value: f32) {
+        self.check(
+            value > 0.0 && value.is_finite(),
+            format!("`{name}` must be positive, got {value}"),
+        );
+    }
+
+    pub fn positive_size(&mut self, name: &str, size: Vec2) {
+        self.check(
+            size.cmpgt(Vec2::ZERO).all() && size.is_finite(),
+            format!("`{name}` must be positive both ways, got {size}"),
+        );
+    }
+
+    pub fn non_negative(&mut self, name: &str, value: f32) {
+        self.check(
+            value >= 0.0 && value.is_finite(),
+            format!("`{name}` must not be negative, got {value}"),
+        );
+    }
+
+    pub fn into_result(self) -> Result<(), ConfigError> {
+        if self.problems.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl From<String> for ConfigError {
+    fn from(problem: String) -> Self {
+        ConfigError {
+            problems: vec![problem],
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.problems.join("; "))
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+// Parses and checks a config, so a broken file fails to load instead of reaching the game
+pub fn parse_config<T: Config>(bytes: &[u8]) -> Result<T, ConfigError> {
+    let config: T = ron::de::from_bytes(bytes).map_err(|error| error.to_string())?;
+    config.validate()?;
+    Ok(config)
+}
+
+pub struct ConfigLoader<T> {
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for ConfigLoader<T> {
+    fn default() -> Self {
+        ConfigLoader {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Config> AssetLoader for ConfigLoader<T> {
+    type Asset = T;
+    type Settings = ();
+    type Error = ConfigError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<T, ConfigError> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(|error| error.to_string())?;
+        parse_config(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+// The file the live config comes from, and whether it has been dealt with,
+// either applied or given up on for the defaults
+#[derive(Resource)]
+pub struct ConfigHandle<T: Asset> {
+    pub a: Handle<T>,
+    pub ready: bool,
+}
+
+// Loads the config at `path`, inside the assets folder, and keeps the resource in sync with it.
+// Until it loads, or if it fails to, the resource holds the defaults.
+pub struct ConfigPlugin<T> {
+    pub path: &'static str,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> ConfigPlugin<T> {
+    pub fn new(path: &'static str) -> Self {
+        ConfigPlugin {
+            path,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Config> Plugin for ConfigPlugin<T> {
+    fn build(&self, app: &mut App) {
+        let path = self.path;
+        app.init_asset::<T>()
+            .register_asset_loader(ConfigLoader::<T>::default())
+            .init_resource::<T>()
+            .add_systems(
+                PreStartup,
+                move |mut commands: Commands, asset_server: Res<AssetServer>| {
+                    commands.insert_resource(ConfigHandle::<T> {
+                        a: asset_server.load(path),
+                        ready: false,
+                    });
+                },
+            )
+            .add_systems(PreUpdate, apply_config::<T>);
+    }
+}
+
+// Copies the config into the resource when it loads, and again on every change to the file
+pub fn apply_config<T: Config>(
+    mut asset_events: EventReader<AssetEvent<T>>,
+    mut handle: ResMut<ConfigHandle<T>>,
+    assets: Res<Assets<T>>,
+    asset_server: Res<AssetServer>,
+    mut config: ResMut<T>,
+) {
+    for asset_event in asset_events.read() {
+        if !asset_event.is_loaded_with_dependencies(&handle.a)
+            && !asset_event.is_modified(&handle.a)
+        {
+            continue;
+        }
+        if let Some(loaded) = assets.get(&handle.a) {
+            *config = loaded.clone();
+            handle.ready = true;
+            info!("Applied {:?}", handle.a.path());
+        }
+    }
+
+    // The asset server has already logged why
+    if !handle.ready && matches!(asset_server.load_state(&handle.a), LoadState::Failed(_)) {
+        warn!("Using the default {}", T::short_type_path());
+        handle.ready = true;
+    }
+}
+
+// Run condition for the systems that need the config from the file, not the defaults
+pub fn config_ready<T: Config>(handle: Option<Res<ConfigHandle<T>>>) -> bool {
+    handle.is_some_and(|handle| handle.ready)
+}
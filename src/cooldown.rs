@@ -0,0 +1,83 @@
+// Timers on entities that count down on their own.
+//
+// A cooldown component wraps a `Timer`, and `CooldownPlugin` ticks every one of them
+// in the `TickCooldowns` set. Gameplay systems only check whether it has finished,
+// and should run after that set to see this tick's time.
+
+use bevy::ecs::component::Mutable;
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::prelude::*;
+use std::marker::PhantomData;
+
+pub trait Cooldown: Component<Mutability = Mutable> {
+    fn timer_mut(&mut self) -> &mut Timer;
+}
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TickCooldowns;
+
+pub fn tick_cooldowns<T: Cooldown>(time: Res<Time>, mut query: Query<&mut T>) {
+    for mut cooldown in &mut query {
+        cooldown.timer_mut().tick(time.delta());
+    }
+}
+
+// Ticks the cooldowns of type `T` in `schedule`, with the time of that schedule
+pub struct CooldownPlugin<T> {
+    pub schedule: InternedScheduleLabel,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> CooldownPlugin<T> {
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        CooldownPlugin {
+            schedule: schedule.intern(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Cooldown> Plugin for CooldownPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(self.schedule, tick_cooldowns::<T>.in_set(TickCooldowns));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[derive(Component)]
+    struct TestCooldown(Timer);
+
+    impl Cooldown for TestCooldown {
+        fn timer_mut(&mut self) -> &mut Timer {
+            &mut self.0
+        }
+    }
+
+    #[test]
+    fn cooldowns_tick_in_their_schedule() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_plugins(CooldownPlugin::<TestCooldown>::new(Update));
+        let entity = app
+            .world_mut()
+            .spawn(TestCooldown(Timer::from_seconds(1.0, TimerMode::Once)))
+            .id();
+
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs(2));
+        app.update();
+
+        assert!(
+            app.world()
+                .get::<TestCooldown>(entity)
+                .unwrap()
+                .0
+                .finished()
+        );
+    }
+}
@@ -0,0 +1,134 @@
+// Runs a game without a window, audio or rendering, for tests.
+//
+// The games' plugins hold everything but what `DefaultPlugins` brings, which the
+// `HeadlessPlugin` stands in for. The window is a plain entity the games can read the size of,
+// and every `update` moves time on by exactly one fixed timestep, so each update runs one
+// `FixedUpdate` tick. The helpers below are shared by the whole game tests of both games.
+
+use crate::config::{Config, ConfigHandle};
+use bevy::audio::AudioLoader;
+use bevy::ecs::system::RunSystemOnce;
+use bevy::input::ButtonState;
+use bevy::input::InputPlugin;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use bevy::text::TextPlugin;
+use bevy::time::TimeUpdateStrategy;
+use bevy::window::{PrimaryWindow, WindowResolution};
+
+pub const HEADLESS_WINDOW_SIZE: Vec2 = Vec2::new(1280.0, 720.0);
+// Ticks the assets get to load from disk in, which can be many on a cold start
+pub const LOADING_TICKS: u32 = 10_000;
+
+// What the games need from `DefaultPlugins` to run, and a stand-in for the window
+pub struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            AssetPlugin::default(),
+            StatesPlugin,
+            InputPlugin,
+            TextPlugin,
+        ))
+        // The games add meshes, materials and sounds, nothing draws or plays them
+        .init_asset::<Image>()
+        .init_asset::<TextureAtlasLayout>()
+        .init_asset::<Mesh>()
+        .init_asset::<ColorMaterial>()
+        .init_asset::<AudioSource>()
+        .init_asset_loader::<AudioLoader>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(
+            Time::<Fixed>::default().timestep(),
+        ));
+
+        app.world_mut().spawn((
+            Window {
+                resolution: WindowResolution::new(HEADLESS_WINDOW_SIZE.x, HEADLESS_WINDOW_SIZE.y),
+                ..default()
+            },
+            PrimaryWindow,
+        ));
+    }
+}
+
+pub fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(HeadlessPlugin);
+    app
+}
+
+pub fn step(app: &mut App, ticks: u32) {
+    for _ in 0..ticks {
+        app.update();
+    }
+}
+
+// Steps until `done` holds, at most `max_ticks` times. Whether it held.
+pub fn step_until(app: &mut App, max_ticks: u32, mut done: impl FnMut(&mut World) -> bool) -> bool {
+    for _ in 0..max_ticks {
+        app.update();
+        if done(app.world_mut()) {
+            return true;
+        }
+    }
+    false
+}
+
+// Steps until the config of type `T` is in, from its file or the defaults if that failed
+pub fn step_until_config_ready<T: Config>(app: &mut App) {
+    let ready = step_until(app, LOADING_TICKS, |world| {
+        world
+            .get_resource::<ConfigHandle<T>>()
+            .is_some_and(|handle| handle.ready)
+    });
+    assert!(ready, "the config never loaded");
+}
+
+// Holds `key` down from the next tick on
+pub fn press(app: &mut App, key: KeyCode) {
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(key);
+}
+
+// Presses and releases `key` over the next tick, through the window's input events. The screens
+// read presses on every frame, not by tick, so they only see it just pressed this way.
+pub fn tap(app: &mut App, key: KeyCode) {
+    for state in [ButtonState::Pressed, ButtonState::Released] {
+        app.world_mut().send_event(KeyboardInput {
+            key_code: key,
+            logical_key: Key::Unidentified(bevy::input::keyboard::NativeKey::Unidentified),
+            state,
+            text: None,
+            repeat: false,
+            window: Entity::PLACEHOLDER,
+        });
+        app.update();
+    }
+}
+
+// Spawns the bundle that `bundle` makes out of the mesh and material assets, like the games'
+// shapes, and returns its entity
+pub fn spawn_with_assets<B: Bundle>(
+    app: &mut App,
+    bundle: impl FnOnce(&mut ResMut<Assets<Mesh>>, &mut ResMut<Assets<ColorMaterial>>) -> B
+    + Send
+    + Sync
+    + 'static,
+) -> Entity {
+    let mut bundle = Some(bundle);
+    app.world_mut()
+        .run_system_once(
+            move |mut commands: Commands,
+                  mut meshes: ResMut<Assets<Mesh>>,
+                  mut materials: ResMut<Assets<ColorMaterial>>| {
+                let bundle = bundle.take().expect("the system runs once");
+                commands.spawn(bundle(&mut meshes, &mut materials)).id()
+            },
+        )
+        .unwrap()
+}
@@ -0,0 +1,876 @@
+// Action based input shared by the games.
+//
+// Gameplay code asks for actions ("is the player firing?") instead of reading keys and
+// buttons, and the bindings from actions to keyboard, mouse and gamepad inputs can be
+// changed while the game runs and are saved to a RON file.
+
+use bevy::ecs::system::SystemParam;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::reflect::TypeRegistry;
+use bevy::reflect::serde::{TypedReflectDeserializer, TypedReflectSerializer};
+use bevy::window::PrimaryWindow;
+use serde::de::DeserializeSeed;
+use std::path::{Path, PathBuf};
+
+// Actions that are either on or off
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ButtonAction {
+    FireBullet,
+    FireLaser,
+    Pause,
+    Confirm,
+    Back,
+    Settings,
+    CycleAimMode,
+}
+
+impl ButtonAction {
+    pub const ALL: [ButtonAction; 7] = [
+        ButtonAction::FireBullet,
+        ButtonAction::FireLaser,
+        ButtonAction::Pause,
+        ButtonAction::Confirm,
+        ButtonAction::Back,
+        ButtonAction::Settings,
+        ButtonAction::CycleAimMode,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ButtonAction::FireBullet => "Fire bullet",
+            ButtonAction::FireLaser => "Fire laser",
+            ButtonAction::Pause => "Pause",
+            ButtonAction::Confirm => "Confirm",
+            ButtonAction::Back => "Back",
+            ButtonAction::Settings => "Settings",
+            ButtonAction::CycleAimMode => "Change aim mode",
+        }
+    }
+}
+
+// Actions with a direction, each component in [-1, 1]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum AxisAction {
+    Thrust,
+    Aim,
+    // The two halves of the keyboard twin-stick layout
+    TwinStickThrust,
+    TwinStickAim,
+}
+
+impl AxisAction {
+    pub const ALL: [AxisAction; 4] = [
+        AxisAction::Thrust,
+        AxisAction::Aim,
+        AxisAction::TwinStickThrust,
+        AxisAction::TwinStickAim,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AxisAction::Thrust => "Thrust",
+            AxisAction::Aim => "Aim",
+            AxisAction::TwinStickThrust => "Twin-stick thrust",
+            AxisAction::TwinStickAim => "Twin-stick aim",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Reflect)]
+pub enum ButtonBinding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButton),
+}
+
+impl ButtonBinding {
+    pub fn is_gamepad(&self) -> bool {
+        matches!(self, ButtonBinding::Gamepad(_))
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            ButtonBinding::Key(key) => format!("{key:?}"),
+            ButtonBinding::Mouse(button) => format!("Mouse {button:?}"),
+            ButtonBinding::Gamepad(button) => format!("{button:?}"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Reflect)]
+pub enum AxisBinding {
+    // One key per direction
+    Keys {
+        up: KeyCode,
+        down: KeyCode,
+        left: KeyCode,
+        right: KeyCode,
+    },
+    // The two axes of a gamepad stick
+    Stick {
+        x: GamepadAxis,
+        y: GamepadAxis,
+    },
+}
+
+impl AxisBinding {
+    // The sticks a player can bind, by their axes
+    pub const STICKS: [AxisBinding; 2] = [
+        AxisBinding::Stick {
+            x: GamepadAxis::LeftStickX,
+            y: GamepadAxis::LeftStickY,
+        },
+        AxisBinding::Stick {
+            x: GamepadAxis::RightStickX,
+            y: GamepadAxis::RightStickY,
+        },
+    ];
+
+    pub fn is_stick(&self) -> bool {
+        matches!(self, AxisBinding::Stick { .. })
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            AxisBinding::Keys {
+                up,
+                down,
+                left,
+                right,
+            } => format!("{up:?}, {down:?}, {left:?}, {right:?}"),
+            AxisBinding::Stick {
+                x: GamepadAxis::LeftStickX,
+                y: GamepadAxis::LeftStickY,
+            } => "Left stick".to_string(),
+            AxisBinding::Stick {
+                x: GamepadAxis::RightStickX,
+                y: GamepadAxis::RightStickY,
+            } => "Right stick".to_string(),
+            AxisBinding::Stick { x, y } => format!("{x:?} and {y:?}"),
+        }
+    }
+}
+
+// Inputs bound to every action. Each action can have several bindings, any of them triggers it.
+#[derive(Resource, Clone, PartialEq, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct InputBindings {
+    pub thrust: Vec<AxisBinding>,
+    pub aim: Vec<AxisBinding>,
+    pub fire_bullet: Vec<ButtonBinding>,
+    pub fire_laser: Vec<ButtonBinding>,
+    pub pause: Vec<ButtonBinding>,
+    pub confirm: Vec<ButtonBinding>,
+    pub back: Vec<ButtonBinding>,
+    pub settings: Vec<ButtonBinding>,
+    // Newer than the first saved bindings, which load with their defaults
+    #[reflect(default = "default_cycle_aim_mode")]
+    pub cycle_aim_mode: Vec<ButtonBinding>,
+    #[reflect(default = "default_twin_stick_thrust")]
+    pub twin_stick_thrust: Vec<AxisBinding>,
+    #[reflect(default = "default_twin_stick_aim")]
+    pub twin_stick_aim: Vec<AxisBinding>,
+    // Sticks closer than this to their centre read as centred
+    pub dead_zone: f32,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        InputBindings {
+            thrust: vec![
+                AxisBinding::Keys {
+                    up: KeyCode::ArrowUp,
+                    down: KeyCode::ArrowDown,
+                    left: KeyCode::ArrowLeft,
+                    right: KeyCode::ArrowRight,
+                },
+                AxisBinding::Stick {
+                    x: GamepadAxis::LeftStickX,
+                    y: GamepadAxis::LeftStickY,
+                },
+            ],
+            aim: vec![AxisBinding::Stick {
+                x: GamepadAxis::RightStickX,
+                y: GamepadAxis::RightStickY,
+            }],
+            fire_bullet: vec![
+                ButtonBinding::Key(KeyCode::Space),
+                ButtonBinding::Gamepad(GamepadButton::RightTrigger),
+            ],
+            fire_laser: vec![
+                ButtonBinding::Key(KeyCode::ShiftLeft),
+                ButtonBinding::Gamepad(GamepadButton::RightTrigger2),
+            ],
+            pause: vec![
+                ButtonBinding::Key(KeyCode::Escape),
+                ButtonBinding::Gamepad(GamepadButton::Start),
+            ],
+            confirm: vec![
+                ButtonBinding::Key(KeyCode::Enter),
+                ButtonBinding::Gamepad(GamepadButton::Start),
+            ],
+            back: vec![
+                ButtonBinding::Key(KeyCode::Escape),
+                ButtonBinding::Gamepad(GamepadButton::Select),
+            ],
+            settings: vec![
+                ButtonBinding::Key(KeyCode::Tab),
+                ButtonBinding::Gamepad(GamepadButton::North),
+            ],
+            cycle_aim_mode: vec![
+                ButtonBinding::Key(KeyCode::KeyM),
+                ButtonBinding::Gamepad(GamepadButton::West),
+            ],
+            twin_stick_thrust: vec![AxisBinding::Keys {
+                up: KeyCode::KeyW,
+                down: KeyCode::KeyS,
+                left: KeyCode::KeyA,
+                right: KeyCode::KeyD,
+            }],
+            twin_stick_aim: vec![AxisBinding::Keys {
+                up: KeyCode::ArrowUp,
+                down: KeyCode::ArrowDown,
+                left: KeyCode::ArrowLeft,
+                right: KeyCode::ArrowRight,
+            }],
+            dead_zone: 0.15,
+        }
+    }
+}
+
+fn default_cycle_aim_mode() -> Vec<ButtonBinding> {
+    InputBindings::default().cycle_aim_mode
+}
+
+fn default_twin_stick_thrust() -> Vec<AxisBinding> {
+    InputBindings::default().twin_stick_thrust
+}
+
+fn default_twin_stick_aim() -> Vec<AxisBinding> {
+    InputBindings::default().twin_stick_aim
+}
+
+impl InputBindings {
+    pub fn buttons(&self, action: ButtonAction) -> &Vec<ButtonBinding> {
+        match action {
+            ButtonAction::FireBullet => &self.fire_bullet,
+            ButtonAction::FireLaser => &self.fire_laser,
+            ButtonAction::Pause => &self.pause,
+            ButtonAction::Confirm => &self.confirm,
+            ButtonAction::Back => &self.back,
+            ButtonAction::Settings => &self.settings,
+            ButtonAction::CycleAimMode => &self.cycle_aim_mode,
+        }
+    }
+
+    pub fn buttons_mut(&mut self, action: ButtonAction) -> &mut Vec<ButtonBinding> {
+        match action {
+            ButtonAction::FireBullet => &mut self.fire_bullet,
+            ButtonAction::FireLaser => &mut self.fire_laser,
+            ButtonAction::Pause => &mut self.pause,
+            ButtonAction::Confirm => &mut self.confirm,
+            ButtonAction::Back => &mut self.back,
+            ButtonAction::Settings => &mut self.settings,
+            ButtonAction::CycleAimMode => &mut self.cycle_aim_mode,
+        }
+    }
+
+    // Names of the inputs bound to the action, to tell the player what to press
+    pub fn describe(&self, action: ButtonAction) -> String {
+        self.buttons(action)
+            .iter()
+            .map(ButtonBinding::name)
+            .collect::<Vec<_>>()
+            .join(" or ")
+    }
+
+    pub fn axes(&self, action: AxisAction) -> &Vec<AxisBinding> {
+        match action {
+            AxisAction::Thrust => &self.thrust,
+            AxisAction::Aim => &self.aim,
+            AxisAction::TwinStickThrust => &self.twin_stick_thrust,
+            AxisAction::TwinStickAim => &self.twin_stick_aim,
+        }
+    }
+
+    pub fn axes_mut(&mut self, action: AxisAction) -> &mut Vec<AxisBinding> {
+        match action {
+            AxisAction::Thrust => &mut self.thrust,
+            AxisAction::Aim => &mut self.aim,
+            AxisAction::TwinStickThrust => &mut self.twin_stick_thrust,
+            AxisAction::TwinStickAim => &mut self.twin_stick_aim,
+        }
+    }
+
+    // Names of the keys or the sticks bound to the action
+    pub fn describe_axis(&self, action: AxisAction, stick: bool) -> String {
+        let names: Vec<String> = self
+            .axes(action)
+            .iter()
+            .filter(|binding| binding.is_stick() == stick)
+            .map(AxisBinding::name)
+            .collect();
+
+        if names.is_empty() {
+            "-".to_string()
+        } else {
+            names.join(" or ")
+        }
+    }
+
+    // Binds `binding` to `action` in place of the binding of the same kind of device,
+    // so rebinding a key keeps the gamepad button and the other way around
+    pub fn rebind(&mut self, action: ButtonAction, binding: ButtonBinding) {
+        let bindings = self.buttons_mut(action);
+
+        match bindings
+            .iter_mut()
+            .find(|bound| bound.is_gamepad() == binding.is_gamepad())
+        {
+            Some(bound) => *bound = binding,
+            None => bindings.push(binding),
+        }
+    }
+
+    // Binds `binding` to `action` in place of the keys or the stick, like `rebind`
+    pub fn rebind_axis(&mut self, action: AxisAction, binding: AxisBinding) {
+        let bindings = self.axes_mut(action);
+
+        match bindings
+            .iter_mut()
+            .find(|bound| bound.is_stick() == binding.is_stick())
+        {
+            Some(bound) => *bound = binding,
+            None => bindings.push(binding),
+        }
+    }
+
+    pub fn to_ron(&self, registry: &TypeRegistry) -> Result<String, String> {
+        let serializer = TypedReflectSerializer::new(self, registry);
+        ron::ser::to_string_pretty(&serializer, ron::ser::PrettyConfig::default())
+            .map_err(|error| error.to_string())
+    }
+
+    pub fn from_ron(text: &str, registry: &TypeRegistry) -> Result<Self, String> {
+        let registration = registry
+            .get(std::any::TypeId::of::<InputBindings>())
+            .ok_or("InputBindings is not registered")?;
+
+        let mut deserializer =
+            ron::de::Deserializer::from_str(text).map_err(|error| error.to_string())?;
+        let reflected = TypedReflectDeserializer::new(registration, registry)
+            .deserialize(&mut deserializer)
+            .map_err(|error| error.to_string())?;
+
+        let mut bindings = InputBindings::from_reflect(reflected.as_ref())
+            .ok_or_else(|| "Malformed input bindings".to_string())?;
+
+        // Out of [0, 1), the dead zone scales sticks by a zero or negative range
+        if !(0.0..1.0).contains(&bindings.dead_zone) {
+            warn!(
+                "Dead zone {} is out of [0, 1), using the default",
+                bindings.dead_zone
+            );
+            bindings.dead_zone = InputBindings::default().dead_zone;
+        }
+
+        Ok(bindings)
+    }
+}
+
+// Scales a stick so the dead zone reads as zero and the rest still spans [0, 1]
+fn apply_dead_zone(stick: Vec2, dead_zone: f32) -> Vec2 {
+    let length = stick.length();
+
+    if length <= dead_zone {
+        Vec2::ZERO
+    } else {
+        stick * ((length - dead_zone) / (1.0 - dead_zone)).min(1.0) / length
+    }
+}
+
+// Device a player controls their ship with. The mouse goes with the keyboard.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum InputSource {
+    Keyboard,
+    Gamepad(Entity),
+}
+
+// The actions of one source during a gameplay tick
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct ActionState {
+    // Bit `i` stands for `ButtonAction::ALL[i]`
+    pub pressed: u8,
+    pub just_pressed: u8,
+    // In the order of `AxisAction::ALL`
+    pub axes: [Vec2; 4],
+    // Where the mouse points in the world, only the keyboard has one
+    pub pointer: Option<Vec2>,
+}
+
+impl ActionState {
+    fn bit(action: ButtonAction) -> u8 {
+        let index = ButtonAction::ALL.iter().position(|a| *a == action);
+        1 << index.unwrap_or_default()
+    }
+
+    pub fn pressed(&self, action: ButtonAction) -> bool {
+        self.pressed & Self::bit(action) != 0
+    }
+
+    pub fn just_pressed(&self, action: ButtonAction) -> bool {
+        self.just_pressed & Self::bit(action) != 0
+    }
+
+    pub fn axis(&self, action: AxisAction) -> Vec2 {
+        let index = AxisAction::ALL.iter().position(|a| *a == action);
+        self.axes[index.unwrap_or_default()]
+    }
+}
+
+// The actions of every source for the gameplay tick being run. While it's set, `ActionInput`
+// reads it instead of the devices, which is how recorded inputs are played back.
+#[derive(Resource, Default)]
+pub struct TickInput {
+    pub a: Option<Vec<(InputSource, ActionState)>>,
+}
+
+// Where the mouse cursor points in the world, if it's over the window
+#[derive(Resource, Default)]
+pub struct PointerPosition {
+    pub a: Option<Vec2>,
+}
+
+// Reads the state of the actions, either from every input device or from a single source
+#[derive(SystemParam)]
+pub struct ActionInput<'w, 's> {
+    bindings: Res<'w, InputBindings>,
+    keyboard: Res<'w, ButtonInput<KeyCode>>,
+    mouse: Res<'w, ButtonInput<MouseButton>>,
+    pointer: Res<'w, PointerPosition>,
+    tick: Option<Res<'w, TickInput>>,
+    gamepads: Query<'w, 's, (Entity, &'static Gamepad)>,
+}
+
+impl ActionInput<'_, '_> {
+    pub fn pressed(&self, action: ButtonAction) -> bool {
+        match self.tick_states(None) {
+            Some(mut states) => states.any(|state| state.pressed(action)),
+            None => self.button(action, None, false),
+        }
+    }
+
+    pub fn just_pressed(&self, action: ButtonAction) -> bool {
+        match self.tick_states(None) {
+            Some(mut states) => states.any(|state| state.just_pressed(action)),
+            None => self.button(action, None, true),
+        }
+    }
+
+    pub fn axis(&self, action: AxisAction) -> Vec2 {
+        match self.tick_states(None) {
+            Some(states) => states
+                .map(|state| state.axis(action))
+                .sum::<Vec2>()
+                .clamp_length_max(1.0),
+            None => self.merged_axis(action, None),
+        }
+    }
+
+    pub fn pressed_by(&self, source: InputSource, action: ButtonAction) -> bool {
+        match self.tick_states(Some(source)) {
+            Some(mut states) => states.any(|state| state.pressed(action)),
+            None => self.button(action, Some(source), false),
+        }
+    }
+
+    pub fn just_pressed_by(&self, source: InputSource, action: ButtonAction) -> bool {
+        match self.tick_states(Some(source)) {
+            Some(mut states) => states.any(|state| state.just_pressed(action)),
+            None => self.button(action, Some(source), true),
+        }
+    }
+
+    pub fn axis_by(&self, source: InputSource, action: AxisAction) -> Vec2 {
+        match self.tick_states(Some(source)) {
+            Some(states) => states
+                .map(|state| state.axis(action))
+                .sum::<Vec2>()
+                .clamp_length_max(1.0),
+            None => self.merged_axis(action, Some(source)),
+        }
+    }
+
+    // Where the mouse cursor points in the world
+    pub fn pointer(&self) -> Option<Vec2> {
+        match self.tick_states(Some(InputSource::Keyboard)) {
+            Some(mut states) => states.find_map(|state| state.pointer),
+            None => self.pointer.a,
+        }
+    }
+
+    // The states of the tick being run that `source` covers, `None` outside of a tick
+    fn tick_states(
+        &self,
+        source: Option<InputSource>,
+    ) -> Option<impl Iterator<Item = &ActionState>> {
+        let states = self.tick.as_ref()?.a.as_ref()?;
+
+        Some(
+            states
+                .iter()
+                .filter(move |(state_source, _)| {
+                    source.is_none_or(|source| source == *state_source)
+                })
+                .map(|(_, state)| state),
+        )
+    }
+
+    // The keyboard and every connected gamepad
+    pub fn sources(&self) -> impl Iterator<Item = InputSource> {
+        std::iter::once(InputSource::Keyboard).chain(
+            self.gamepads
+                .iter()
+                .map(|(entity, _)| InputSource::Gamepad(entity)),
+        )
+    }
+
+    // What `source` holds on the devices right now, whatever tick is being run.
+    // Presses are left for the caller to find, they depend on what it read last.
+    pub fn live_state(&self, source: InputSource) -> ActionState {
+        let mut state = ActionState::default();
+
+        for (i, action) in ButtonAction::ALL.into_iter().enumerate() {
+            if self.button(action, Some(source), false) {
+                state.pressed |= 1 << i;
+            }
+        }
+        for (i, action) in AxisAction::ALL.into_iter().enumerate() {
+            state.axes[i] = self.merged_axis(action, Some(source));
+        }
+        if source == InputSource::Keyboard {
+            state.pointer = self.pointer.a;
+        }
+
+        state
+    }
+
+    fn reads_keyboard(source: Option<InputSource>) -> bool {
+        matches!(source, None | Some(InputSource::Keyboard))
+    }
+
+    // The gamepads `source` covers, all of them if there's no source
+    fn gamepads_of(&self, source: Option<InputSource>) -> impl Iterator<Item = &Gamepad> {
+        self.gamepads
+            .iter()
+            .filter(move |(entity, _)| match source {
+                None => true,
+                Some(InputSource::Gamepad(gamepad_entity)) => *entity == gamepad_entity,
+                Some(InputSource::Keyboard) => false,
+            })
+            .map(|(_, gamepad)| gamepad)
+    }
+
+    fn button(&self, action: ButtonAction, source: Option<InputSource>, just: bool) -> bool {
+        let keyboard = Self::reads_keyboard(source);
+
+        self.bindings
+            .buttons(action)
+            .iter()
+            .any(|binding| match *binding {
+                ButtonBinding::Key(key) if just => keyboard && self.keyboard.just_pressed(key),
+                ButtonBinding::Key(key) => keyboard && self.keyboard.pressed(key),
+                ButtonBinding::Mouse(button) if just => keyboard && self.mouse.just_pressed(button),
+                ButtonBinding::Mouse(button) => keyboard && self.mouse.pressed(button),
+                ButtonBinding::Gamepad(button) => self.gamepads_of(source).any(|gamepad| {
+                    if just {
+                        gamepad.just_pressed(button)
+                    } else {
+                        gamepad.pressed(button)
+                    }
+                }),
+            })
+    }
+
+    // Merges every binding of the action, the result is never longer than 1
+    fn merged_axis(&self, action: AxisAction, source: Option<InputSource>) -> Vec2 {
+        let mut axis = Vec2::ZERO;
+
+        for binding in self.bindings.axes(action) {
+            match *binding {
+                AxisBinding::Keys {
+                    up,
+                    down,
+                    left,
+                    right,
+                } => {
+                    if Self::reads_keyboard(source) {
+                        let key = |key: KeyCode| f32::from(u8::from(self.keyboard.pressed(key)));
+                        axis += Vec2::new(key(right) - key(left), key(up) - key(down));
+                    }
+                }
+                AxisBinding::Stick { x, y } => {
+                    for gamepad in self.gamepads_of(source) {
+                        let stick = Vec2::new(
+                            gamepad.get(x).unwrap_or_default(),
+                            gamepad.get(y).unwrap_or_default(),
+                        );
+                        axis += apply_dead_zone(stick, self.bindings.dead_zone);
+                    }
+                }
+            }
+        }
+
+        axis.clamp_length_max(1.0)
+    }
+
+    // The first key or button pressed this frame, used to rebind an action
+    pub fn captured_binding(&self) -> Option<ButtonBinding> {
+        if let Some(key) = self.keyboard.get_just_pressed().next() {
+            return Some(ButtonBinding::Key(*key));
+        }
+        if let Some(button) = self.mouse.get_just_pressed().next() {
+            return Some(ButtonBinding::Mouse(*button));
+        }
+        self.gamepads.iter().find_map(|(_, gamepad)| {
+            gamepad
+                .get_just_pressed()
+                .next()
+                .map(|button| ButtonBinding::Gamepad(*button))
+        })
+    }
+
+    // The first key pressed this frame, used to rebind the directions of an axis action
+    pub fn captured_key(&self) -> Option<KeyCode> {
+        self.keyboard.get_just_pressed().next().copied()
+    }
+
+    // The first stick pushed further than `threshold`, used to rebind an axis action
+    pub fn captured_stick(&self, threshold: f32) -> Option<AxisBinding> {
+        self.gamepads.iter().find_map(|(_, gamepad)| {
+            AxisBinding::STICKS.into_iter().find(|stick| {
+                let AxisBinding::Stick { x, y } = *stick else {
+                    return false;
+                };
+                let pushed = Vec2::new(
+                    gamepad.get(x).unwrap_or_default(),
+                    gamepad.get(y).unwrap_or_default(),
+                );
+                pushed.length() > threshold
+            })
+        })
+    }
+
+    pub fn bindings(&self) -> &InputBindings {
+        &self.bindings
+    }
+}
+
+// File the bindings are loaded from at startup and saved to after rebinding
+#[derive(Resource, Clone, Deref)]
+pub struct InputBindingsFile {
+    pub a: PathBuf,
+}
+
+// Loads the bindings saved in `path`, falling back to the defaults
+pub struct ActionInputPlugin {
+    pub path: &'static str,
+}
+
+impl Plugin for ActionInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<InputBindings>()
+            .init_resource::<InputBindings>()
+            .init_resource::<PointerPosition>()
+            .insert_resource(InputBindingsFile {
+                a: PathBuf::from(self.path),
+            })
+            .add_systems(PreStartup, load_input_bindings)
+            .add_systems(PreUpdate, track_pointer.after(InputSystem));
+    }
+}
+
+pub fn track_pointer(
+    mut pointer: ResMut<PointerPosition>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+) {
+    pointer.a = q_window
+        .single()
+        .ok()
+        .and_then(Window::cursor_position)
+        .and_then(|cursor_position| {
+            let (camera, camera_transform) = q_camera.single().ok()?;
+            camera
+                .viewport_to_world_2d(camera_transform, cursor_position)
+                .ok()
+        });
+}
+
+pub fn load_input_bindings(
+    mut bindings: ResMut<InputBindings>,
+    file: Res<InputBindingsFile>,
+    registry: Res<AppTypeRegistry>,
+) {
+    let Ok(text) = std::fs::read_to_string(&file.a) else {
+        info!("No input bindings at {:?}, using the defaults", file.a);
+        return;
+    };
+
+    match InputBindings::from_ron(&text, &registry.read()) {
+        Ok(loaded) => *bindings = loaded,
+        Err(error) => warn!("Failed to load input bindings from {:?}: {error}", file.a),
+    }
+}
+
+pub fn save_input_bindings(
+    bindings: Res<InputBindings>,
+    file: Res<InputBindingsFile>,
+    registry: Res<AppTypeRegistry>,
+) {
+    let result = bindings
+        .to_ron(&registry.read())
+        .and_then(|text| write_file(&file.a, &text).map_err(|error| error.to_string()));
+
+    if let Err(error) = result {
+        warn!("Failed to save input bindings to {:?}: {error}", file.a);
+    }
+}
+
+fn write_file(path: &Path, text: &str) -> std::io::Result<()> {
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory)?;
+    }
+    std::fs::write(path, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> TypeRegistry {
+        let mut registry = TypeRegistry::default();
+        registry.register::<InputBindings>();
+        registry
+    }
+
+    #[test]
+    fn bindings_survive_a_round_trip_through_ron() {
+        let registry = registry();
+        let mut bindings = InputBindings::default();
+        bindings.rebind(
+            ButtonAction::FireBullet,
+            ButtonBinding::Mouse(MouseButton::Left),
+        );
+        bindings.dead_zone = 0.3;
+
+        let text = bindings.to_ron(&registry).unwrap();
+
+        assert_eq!(InputBindings::from_ron(&text, &registry), Ok(bindings));
+    }
+
+    // Saved before the aim mode and twin-stick bindings
+    const OLD_BINDINGS: &str = "(
+        thrust: [Keys(up: KeyW, down: KeyS, left: KeyA, right: KeyD)],
+        aim: [Stick(x: RightStickX, y: RightStickY)],
+        fire_bullet: [Key(Space)],
+        fire_laser: [Key(ShiftLeft)],
+        pause: [Key(Escape)],
+        confirm: [Key(Enter)],
+        back: [Key(Escape)],
+        settings: [Key(Tab)],
+        dead_zone: 0.2,
+    )";
+
+    #[test]
+    fn old_bindings_load_with_the_new_defaults() {
+        let loaded = InputBindings::from_ron(OLD_BINDINGS, &registry()).unwrap();
+        let default = InputBindings::default();
+
+        assert_eq!(
+            loaded.thrust,
+            vec![AxisBinding::Keys {
+                up: KeyCode::KeyW,
+                down: KeyCode::KeyS,
+                left: KeyCode::KeyA,
+                right: KeyCode::KeyD,
+            }]
+        );
+        assert_eq!(loaded.dead_zone, 0.2);
+        assert_eq!(loaded.cycle_aim_mode, default.cycle_aim_mode);
+        assert_eq!(loaded.twin_stick_thrust, default.twin_stick_thrust);
+        assert_eq!(loaded.twin_stick_aim, default.twin_stick_aim);
+    }
+
+    #[test]
+    fn malformed_bindings_are_an_error() {
+        assert!(InputBindings::from_ron("(thrust: 3)", &registry()).is_err());
+    }
+
+    #[test]
+    fn out_of_range_dead_zones_fall_back_to_the_default() {
+        let registry = registry();
+        let default_dead_zone = InputBindings::default().dead_zone;
+
+        for dead_zone in [-0.5, 1.0, f32::NAN] {
+            let bindings = InputBindings {
+                dead_zone,
+                ..default()
+            };
+            let text = bindings.to_ron(&registry).unwrap();
+
+            let loaded = InputBindings::from_ron(&text, &registry).unwrap();
+            assert_eq!(loaded.dead_zone, default_dead_zone);
+        }
+    }
+
+    #[test]
+    fn rebinding_keeps_the_other_device() {
+        let mut bindings = InputBindings::default();
+
+        bindings.rebind(ButtonAction::FireLaser, ButtonBinding::Key(KeyCode::KeyX));
+
+        assert_eq!(
+            bindings.fire_laser,
+            vec![
+                ButtonBinding::Key(KeyCode::KeyX),
+                ButtonBinding::Gamepad(GamepadButton::RightTrigger2),
+            ]
+        );
+    }
+
+    #[test]
+    fn rebinding_an_axis_keeps_the_other_kind() {
+        let mut bindings = InputBindings::default();
+        let keys = AxisBinding::Keys {
+            up: KeyCode::KeyI,
+            down: KeyCode::KeyK,
+            left: KeyCode::KeyJ,
+            right: KeyCode::KeyL,
+        };
+
+        bindings.rebind_axis(AxisAction::Thrust, keys);
+        bindings.rebind_axis(AxisAction::Aim, keys);
+
+        assert_eq!(
+            bindings.thrust,
+            vec![keys, InputBindings::default().thrust[1]]
+        );
+        assert_eq!(bindings.aim, vec![InputBindings::default().aim[0], keys]);
+    }
+
+    #[test]
+    fn dead_zone_reads_as_centred() {
+        assert_eq!(apply_dead_zone(Vec2::new(0.1, 0.0), 0.15), Vec2::ZERO);
+    }
+
+    #[test]
+    fn dead_zone_keeps_the_full_range() {
+        let stick = apply_dead_zone(Vec2::new(0.0, 1.0), 0.15);
+        assert!((stick - Vec2::Y).length() < 1e-6);
+
+        let halfway = apply_dead_zone(Vec2::new(0.575, 0.0), 0.15);
+        assert!((halfway.x - 0.5).abs() < 1e-6);
+    }
+}
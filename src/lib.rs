@@ -0,0 +1,18 @@
+//! Building blocks shared by the games of the sandbox.
+//!
+//! Each module is a small plugin, or a set of helpers, that both examples use,
+//! so a fix to one of them lands in both games.
+
+pub mod args;
+pub mod collision;
+pub mod collision_sound;
+pub mod config;
+pub mod cooldown;
+pub mod headless;
+pub mod input;
+pub mod replay;
+pub mod rng;
+pub mod scoreboard;
+pub mod screen;
+pub mod settings;
+pub mod velocity;
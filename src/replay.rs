@@ -0,0 +1,542 @@
+// Recording and playback of the gameplay inputs, to reproduce a run from a bug report.
+//
+// `--record <file>` saves the seed of the run and the actions of every source on every
+// gameplay tick, `--replay <file>` feeds them back to the game instead of the devices.
+// Every `CHECKSUM_INTERVAL` ticks the positions of the shapes are hashed, and a replay
+// that doesn't land on the recorded hashes reports where it diverged.
+//
+// Only the first run of a session is recorded or played, and a replay only matches its
+// recording with the same config, and gamepads connected in the same order.
+
+use crate::args::arg_value;
+use crate::collision::Shape;
+use crate::input::{ActionInput, ActionState, InputSource, TickInput};
+use crate::rng::GameRng;
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+pub const CHECKSUM_INTERVAL: u32 = 60;
+// An hour at the default 64 ticks a second. Recordings stop there, and longer replays are
+// refused, so a corrupt run length can't ask for more ticks than fit in memory.
+pub const MAX_TICKS: usize = 64 * 60 * 60;
+// The keyboard and more gamepads than anyone has
+pub const MAX_SOURCES: usize = 16;
+
+const MAGIC: &[u8; 4] = b"BSRP";
+const VERSION: u8 = 1;
+
+// A source as saved, gamepads by the order they showed up in, since entities change between runs
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RecordedSource {
+    Keyboard,
+    Gamepad(u8),
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Recording {
+    pub seed: u64,
+    // The actions of every source, tick by tick
+    pub ticks: Vec<Vec<(RecordedSource, ActionState)>>,
+    // Hash of the positions at the start of a tick, by tick
+    pub checksums: Vec<(u32, u64)>,
+}
+
+impl Recording {
+    // Little endian, with runs of identical ticks stored once, as inputs rarely change every tick
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+
+        let runs = self.ticks.chunk_by(|a, b| a == b).collect::<Vec<_>>();
+        bytes.extend_from_slice(&(runs.len() as u32).to_le_bytes());
+        for run in runs {
+            bytes.extend_from_slice(&(run.len() as u32).to_le_bytes());
+            write_tick(&mut bytes, &run[0]);
+        }
+
+        bytes.extend_from_slice(&(self.checksums.len() as u32).to_le_bytes());
+        for (tick, checksum) in &self.checksums {
+            bytes.extend_from_slice(&tick.to_le_bytes());
+            bytes.extend_from_slice(&checksum.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = ByteReader { bytes };
+        if reader.take(4)? != MAGIC {
+            return Err("Not a replay file".to_string());
+        }
+        let version = reader.u8()?;
+        if version != VERSION {
+            return Err(format!("Unsupported replay version {version}"));
+        }
+
+        let mut recording = Recording {
+            seed: reader.u64()?,
+            ..default()
+        };
+
+        for _ in 0..reader.u32()? {
+            let length = reader.u32()? as usize;
+            if length > MAX_TICKS - recording.ticks.len() {
+                return Err(format!("The replay is longer than {MAX_TICKS} ticks"));
+            }
+            let tick = read_tick(&mut reader)?;
+            recording.ticks.extend(std::iter::repeat_n(tick, length));
+        }
+
+        for _ in 0..reader.u32()? {
+            let tick = reader.u32()?;
+            recording.checksums.push((tick, reader.u64()?));
+        }
+
+        if !reader.bytes.is_empty() {
+            return Err("Unexpected data after the replay".to_string());
+        }
+        Ok(recording)
+    }
+}
+
+fn write_tick(bytes: &mut Vec<u8>, tick: &[(RecordedSource, ActionState)]) {
+    bytes.push(tick.len() as u8);
+
+    for (source, state) in tick {
+        // 0 is the keyboard, gamepads come after it
+        bytes.push(match source {
+            RecordedSource::Keyboard => 0,
+            RecordedSource::Gamepad(index) => index + 1,
+        });
+        bytes.push(state.pressed);
+        bytes.push(state.just_pressed);
+        for axis in state.axes {
+            bytes.extend_from_slice(&axis.x.to_le_bytes());
+            bytes.extend_from_slice(&axis.y.to_le_bytes());
+        }
+        match state.pointer {
+            Some(pointer) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&pointer.x.to_le_bytes());
+                bytes.extend_from_slice(&pointer.y.to_le_bytes());
+            }
+            None => bytes.push(0),
+        }
+    }
+}
+
+fn read_tick(reader: &mut ByteReader) -> Result<Vec<(RecordedSource, ActionState)>, String> {
+    let count = reader.u8()?;
+    if count as usize > MAX_SOURCES {
+        return Err(format!("A tick has more than {MAX_SOURCES} sources"));
+    }
+    let mut tick = Vec::with_capacity(count as usize);
+
+    for _ in 0..count {
+        let source = match reader.u8()? {
+            0 => RecordedSource::Keyboard,
+            index => RecordedSource::Gamepad(index - 1),
+        };
+        let mut state = ActionState {
+            pressed: reader.u8()?,
+            just_pressed: reader.u8()?,
+            ..default()
+        };
+        for axis in &mut state.axes {
+            *axis = reader.vec2()?;
+        }
+        if reader.u8()? != 0 {
+            state.pointer = Some(reader.vec2()?);
+        }
+        tick.push((source, state));
+    }
+
+    Ok(tick)
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() < count {
+            return Err("The replay file ends early".to_string());
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn vec2(&mut self) -> Result<Vec2, String> {
+        let x = f32::from_le_bytes(self.array()?);
+        let y = f32::from_le_bytes(self.array()?);
+        Ok(Vec2::new(x, y))
+    }
+}
+
+// Hash of where every shape is. The order entities are visited in doesn't change it.
+pub fn position_checksum(positions: impl IntoIterator<Item = Vec3>) -> u64 {
+    positions.into_iter().fold(0u64, |checksum, position| {
+        let bits = (u64::from(position.x.to_bits()) << 32) | u64::from(position.y.to_bits());
+        checksum.wrapping_add(mix(bits))
+    })
+}
+
+// The SplitMix64 finalizer, so nearby positions hash far apart
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ReplayMode {
+    // Inputs come from the devices
+    Live,
+    Record,
+    Play,
+}
+
+#[derive(Resource)]
+pub struct Replay {
+    pub mode: ReplayMode,
+    pub path: PathBuf,
+    pub recording: Recording,
+    // Ticks recorded or played so far
+    pub tick: u32,
+    pub diverged: bool,
+    // Gamepads in the order they showed up, a recorded gamepad is its place in here
+    gamepads: Vec<Entity>,
+    // Actions each source held on the last tick, to find the presses
+    held: HashMap<InputSource, u8>,
+    // What the tick being run reads
+    current: Vec<(InputSource, ActionState)>,
+}
+
+impl Replay {
+    pub fn new(mode: ReplayMode, path: PathBuf, recording: Recording) -> Self {
+        Replay {
+            mode,
+            path,
+            recording,
+            tick: 0,
+            diverged: false,
+            gamepads: Vec::new(),
+            held: HashMap::new(),
+            current: Vec::new(),
+        }
+    }
+
+    // From `--record <file>` or `--replay <file>`, live if there's neither
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Self {
+        let args = args.into_iter().collect::<Vec<_>>();
+
+        if let Some(path) = arg_value(args.clone(), "--replay") {
+            let path = PathBuf::from(path);
+            match std::fs::read(&path)
+                .map_err(|error| error.to_string())
+                .and_then(|bytes| Recording::from_bytes(&bytes))
+            {
+                Ok(recording) => return Replay::new(ReplayMode::Play, path, recording),
+                Err(error) => warn!("Failed to load the replay {path:?}: {error}"),
+            }
+        } else if let Some(path) = arg_value(args, "--record") {
+            return Replay::new(ReplayMode::Record, PathBuf::from(path), default());
+        }
+
+        Replay::new(ReplayMode::Live, PathBuf::new(), default())
+    }
+
+    fn recorded_source(&self, source: InputSource) -> RecordedSource {
+        match source {
+            InputSource::Keyboard => RecordedSource::Keyboard,
+            InputSource::Gamepad(entity) => RecordedSource::Gamepad(
+                self.gamepads
+                    .iter()
+                    .position(|gamepad| *gamepad == entity)
+                    .unwrap_or_default() as u8,
+            ),
+        }
+    }
+
+    fn live_source(&self, source: RecordedSource) -> Option<InputSource> {
+        match source {
+            RecordedSource::Keyboard => Some(InputSource::Keyboard),
+            RecordedSource::Gamepad(index) => self
+                .gamepads
+                .get(index as usize)
+                .map(|entity| InputSource::Gamepad(*entity)),
+        }
+    }
+}
+
+// Run condition for the systems that stand in for a player while a replay plays
+pub fn replaying(replay: Option<Res<Replay>>) -> bool {
+    replay.is_some_and(|replay| replay.mode == ReplayMode::Play)
+}
+
+// The gameplay systems run between the two sets, which the game orders them against
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReplaySet {
+    // Reads the actions of the tick, from the devices or the replay
+    Feed,
+    // Hands `ActionInput` back to the devices
+    Release,
+}
+
+// Records or hashes the positions the tick starts from
+pub fn check_positions(mut replay: ResMut<Replay>, q_shape: Query<&Transform, With<Shape>>) {
+    let tick = replay.tick;
+    if replay.mode == ReplayMode::Live || !tick.is_multiple_of(CHECKSUM_INTERVAL) {
+        return;
+    }
+
+    let checksum = position_checksum(q_shape.iter().map(|transform| transform.translation));
+
+    match replay.mode {
+        ReplayMode::Record => replay.recording.checksums.push((tick, checksum)),
+        ReplayMode::Play => {
+            let recorded = replay
+                .recording
+                .checksums
+                .iter()
+                .find(|(recorded_tick, _)| *recorded_tick == tick);
+            if let Some((_, recorded)) = recorded
+                && *recorded != checksum
+                && !replay.diverged
+            {
+                warn!("The replay diverged from its recording by tick {tick}");
+                replay.diverged = true;
+            }
+        }
+        ReplayMode::Live => {}
+    }
+}
+
+// Works out the actions of every source for the tick, and records them
+pub fn read_tick_input(
+    mut replay: ResMut<Replay>,
+    action_input: ActionInput,
+    q_gamepad: Query<Entity, With<Gamepad>>,
+) {
+    let mut gamepads = q_gamepad.iter().collect::<Vec<_>>();
+    gamepads.sort();
+    for gamepad in gamepads {
+        if !replay.gamepads.contains(&gamepad) {
+            replay.gamepads.push(gamepad);
+        }
+    }
+
+    let tick = replay.tick as usize;
+    if replay.mode == ReplayMode::Play && tick >= replay.recording.ticks.len() {
+        match replay.diverged {
+            true => info!("The replay ended after {tick} ticks, it diverged on the way"),
+            false => info!("The replay ended after {tick} ticks, back to live input"),
+        }
+        replay.mode = ReplayMode::Live;
+    }
+
+    replay.current = match replay.mode {
+        ReplayMode::Play => replay.recording.ticks[tick]
+            .iter()
+            .filter_map(|(source, state)| Some((replay.live_source(*source)?, *state)))
+            .collect(),
+        ReplayMode::Live | ReplayMode::Record => {
+            let mut current = Vec::new();
+            for source in action_input.sources() {
+                let mut state = action_input.live_state(source);
+                // Pressed since the last tick, not the last frame, or a frame running two ticks
+                // would press twice
+                let held = replay.held.get(&source).copied().unwrap_or_default();
+                state.just_pressed = state.pressed & !held;
+                current.push((source, state));
+            }
+            current
+        }
+    };
+
+    replay.held = replay
+        .current
+        .iter()
+        .map(|(source, state)| (*source, state.pressed))
+        .collect();
+
+    if replay.mode == ReplayMode::Record && replay.recording.ticks.len() < MAX_TICKS {
+        let recorded = replay
+            .current
+            .iter()
+            .map(|(source, state)| (replay.recorded_source(*source), *state))
+            .collect();
+        replay.recording.ticks.push(recorded);
+
+        if replay.recording.ticks.len() == MAX_TICKS {
+            warn!("The recording is full at {MAX_TICKS} ticks, the rest of the run isn't recorded");
+        }
+    }
+    replay.tick += 1;
+}
+
+pub fn feed_tick_input(replay: Res<Replay>, mut tick_input: ResMut<TickInput>) {
+    tick_input.a = Some(replay.current.clone());
+}
+
+pub fn release_tick_input(mut tick_input: ResMut<TickInput>) {
+    tick_input.a = None;
+}
+
+// Uses the seed of the replay for the run it plays
+pub fn use_recorded_seed(replay: Res<Replay>, mut rng: ResMut<GameRng>) {
+    if replay.mode == ReplayMode::Play {
+        rng.fixed_seed = Some(replay.recording.seed);
+    }
+}
+
+// Writes the recording, once. Games call it when a run ends, and it's called on exit.
+pub fn save_recording(mut replay: ResMut<Replay>, rng: Res<GameRng>) {
+    if replay.mode != ReplayMode::Record {
+        return;
+    }
+    replay.mode = ReplayMode::Live;
+    replay.recording.seed = rng.seed();
+
+    let ticks = replay.recording.ticks.len();
+    match std::fs::write(&replay.path, replay.recording.to_bytes()) {
+        Ok(()) => info!("Saved {ticks} ticks to the replay {:?}", replay.path),
+        Err(error) => warn!("Failed to save the replay {:?}: {error}", replay.path),
+    }
+}
+
+// Runs the gameplay ticks of `schedule` from recorded inputs with `--replay`, and records
+// them with `--record`. Needs the `ActionInputPlugin` and the `RngPlugin`.
+pub struct ReplayPlugin {
+    pub schedule: InternedScheduleLabel,
+}
+
+impl ReplayPlugin {
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        ReplayPlugin {
+            schedule: schedule.intern(),
+        }
+    }
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Replay::from_args(std::env::args().skip(1)))
+            .init_resource::<TickInput>()
+            .configure_sets(self.schedule, ReplaySet::Feed.before(ReplaySet::Release))
+            .add_systems(PreStartup, use_recorded_seed)
+            .add_systems(
+                self.schedule,
+                (check_positions, read_tick_input, feed_tick_input)
+                    .chain()
+                    .in_set(ReplaySet::Feed),
+            )
+            .add_systems(self.schedule, release_tick_input.in_set(ReplaySet::Release))
+            .add_systems(Last, save_recording.run_if(on_event::<AppExit>));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording() -> Recording {
+        let firing = ActionState {
+            pressed: 0b1,
+            just_pressed: 0b1,
+            axes: [Vec2::new(0.5, -1.0), Vec2::ZERO, Vec2::ZERO, Vec2::Y],
+            pointer: Some(Vec2::new(12.0, 34.5)),
+        };
+        let idle = vec![(RecordedSource::Keyboard, ActionState::default())];
+
+        Recording {
+            seed: 99,
+            ticks: vec![
+                idle.clone(),
+                idle.clone(),
+                vec![
+                    (RecordedSource::Keyboard, firing),
+                    (RecordedSource::Gamepad(1), ActionState::default()),
+                ],
+                idle,
+            ],
+            checksums: vec![(0, 7), (60, u64::MAX)],
+        }
+    }
+
+    #[test]
+    fn recording_survives_a_round_trip() {
+        let recording = recording();
+
+        assert_eq!(Recording::from_bytes(&recording.to_bytes()), Ok(recording));
+    }
+
+    #[test]
+    fn identical_ticks_are_stored_once() {
+        let mut recording = recording();
+        let short = recording.to_bytes().len();
+
+        let idle = recording.ticks[0].clone();
+        recording.ticks.extend(std::iter::repeat_n(idle, 1000));
+
+        assert_eq!(recording.to_bytes().len(), short);
+    }
+
+    #[test]
+    fn truncated_files_are_an_error() {
+        let bytes = recording().to_bytes();
+
+        assert!(Recording::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Recording::from_bytes(b"nope").is_err());
+    }
+
+    #[test]
+    fn overlong_replays_are_an_error() {
+        let mut recording = recording();
+        recording.ticks = vec![Vec::new(); MAX_TICKS];
+        assert!(Recording::from_bytes(&recording.to_bytes()).is_ok());
+
+        // A few bytes asking for billions of ticks
+        recording.ticks = vec![Vec::new()];
+        let mut bytes = recording.to_bytes();
+        let run_length = MAGIC.len() + 1 + 8 + 4;
+        bytes[run_length..run_length + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(Recording::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn checksum_ignores_the_order_but_not_the_positions() {
+        let a = Vec3::new(1.0, 2.0, 0.0);
+        let b = Vec3::new(-3.0, 4.5, 1.0);
+
+        assert_eq!(position_checksum([a, b]), position_checksum([b, a]));
+        assert_ne!(
+            position_checksum([a, b]),
+            position_checksum([a, b + Vec3::X * 0.001])
+        );
+    }
+}
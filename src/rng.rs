@@ -0,0 +1,192 @@
+// Seeded random numbers, so a run can be played again.
+//
+// Every random decision of a game goes through `GameRng`, which is reseeded when a run starts.
+// The seed comes from `--seed <n>` on the command line, then from the game's config,
+// and is fresh otherwise. It is logged, so a run can be reproduced with `--seed`.
+
+use crate::args::arg_value;
+use bevy::prelude::*;
+
+// SplitMix64, small and fast, and the same on every platform
+#[derive(Resource, Debug, Clone)]
+pub struct GameRng {
+    state: u64,
+    seed: u64,
+    // Seed given on the command line, it wins over the config
+    pub fixed_seed: Option<u64>,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        GameRng {
+            state: seed,
+            seed,
+            fixed_seed: None,
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn reseed(&mut self, seed: u64) {
+        self.state = seed;
+        self.seed = seed;
+    }
+
+    // Reseeds for a new run and returns the seed used
+    pub fn start_run(&mut self, config_seed: Option<u64>) -> u64 {
+        let seed = self.fixed_seed.or(config_seed).unwrap_or_else(fresh_seed);
+        self.reseed(seed);
+        info!("Random seed: {seed}");
+        seed
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // A number in [0, 1)
+    pub fn unit(&mut self) -> f32 {
+        // The 24 top bits are as many as an f32 can hold exactly
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    // An index in [0, len), len must not be 0
+    pub fn index(&mut self, len: usize) -> usize {
+        (self.next_u64() % len as u64) as usize
+    }
+
+    // An index picked with a chance proportional to its weight.
+    // `None` if no weight is positive.
+    pub fn weighted_index(&mut self, weights: &[f32]) -> Option<usize> {
+        let total_weight: f32 = weights.iter().filter(|weight| **weight > 0.0).sum();
+        if total_weight <= 0.0 {
+            return None;
+        }
+
+        let mut roll = self.unit() * total_weight;
+        let last_positive = weights.iter().rposition(|weight| *weight > 0.0)?;
+
+        for (i, &weight) in weights.iter().enumerate() {
+            if weight <= 0.0 {
+                continue;
+            }
+            if roll < weight {
+                return Some(i);
+            }
+            roll -= weight;
+        }
+
+        // Rounding can leave a sliver past the last weight
+        Some(last_positive)
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        GameRng::new(0)
+    }
+}
+
+// Entropy from the system, or the clock if there's none
+fn fresh_seed() -> u64 {
+    getrandom::u64().unwrap_or_else(|error| {
+        warn!("Failed to get a random seed ({error}), using the clock");
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|time| time.as_nanos() as u64)
+            .unwrap_or_default()
+    })
+}
+
+// Reads `--seed <n>` or `--seed=<n>`
+pub fn seed_from_args(args: impl IntoIterator<Item = String>) -> Option<u64> {
+    let value = arg_value(args, "--seed")?;
+
+    match value.parse() {
+        Ok(seed) => Some(seed),
+        Err(_) => {
+            warn!("Ignoring `--seed {value}`, the seed must be a whole number");
+            None
+        }
+    }
+}
+
+// Adds the `GameRng`, with the seed from the command line if there is one
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameRng {
+            fixed_seed: seed_from_args(std::env::args().skip(1)),
+            ..default()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn same_seed_same_numbers() {
+        let mut a = GameRng::new(42);
+        let mut b = GameRng::new(42);
+
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+
+        a.reseed(7);
+        b.reseed(7);
+        assert_eq!(a.unit(), b.unit());
+    }
+
+    #[test]
+    fn units_stay_in_range() {
+        let mut rng = GameRng::new(1);
+
+        for _ in 0..10_000 {
+            let unit = rng.unit();
+            assert!((0.0..1.0).contains(&unit));
+        }
+    }
+
+    #[test]
+    fn weighted_index_skips_empty_weights() {
+        let mut rng = GameRng::new(3);
+
+        for _ in 0..1000 {
+            assert_eq!(rng.weighted_index(&[0.0, 2.0, 0.0]), Some(1));
+        }
+        assert_eq!(rng.weighted_index(&[0.0, 0.0]), None);
+    }
+
+    #[test]
+    fn command_line_seed_wins_over_the_config() {
+        let mut rng = GameRng::new(0);
+        rng.fixed_seed = Some(5);
+
+        assert_eq!(rng.start_run(Some(9)), 5);
+
+        rng.fixed_seed = None;
+        assert_eq!(rng.start_run(Some(9)), 9);
+    }
+
+    #[test]
+    fn seed_is_read_from_the_arguments() {
+        assert_eq!(seed_from_args(args("--seed 12")), Some(12));
+        assert_eq!(seed_from_args(args("--fullscreen --seed=34")), Some(34));
+        assert_eq!(seed_from_args(args("--seed nope")), None);
+        assert_eq!(seed_from_args(args("")), None);
+    }
+}
@@ -0,0 +1,75 @@
+// The scoreboard in the top left corner of the screen.
+//
+// It's a column of lines, each a label followed by one or more values in text spans,
+// so games update a value with `TextUiWriter::text(line, span)`.
+
+use bevy::prelude::*;
+
+#[derive(Component)]
+pub struct ScoreboardUi;
+
+#[derive(Resource, Clone, Debug)]
+pub struct ScoreboardStyle {
+    pub font_size: f32,
+    pub padding: Val,
+    pub text_color: Color,
+    pub value_color: Color,
+}
+
+impl ScoreboardStyle {
+    // The column the lines are stacked in
+    pub fn root(&self) -> impl Bundle {
+        (
+            Node {
+                position_type: PositionType::Absolute,
+                top: self.padding,
+                left: self.padding,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            ScoreboardUi,
+        )
+    }
+
+    // A line with a single value, in span 1
+    pub fn line(&self, label: impl Into<String>, value: impl Into<String>) -> impl Bundle {
+        (
+            self.label(label, self.text_color),
+            children![self.value(value, self.value_color)],
+        )
+    }
+
+    // The start of a line, its values are children made with `value`
+    pub fn label(&self, text: impl Into<String>, color: Color) -> impl Bundle {
+        (
+            Text::new(text),
+            TextFont {
+                font_size: self.font_size,
+                ..default()
+            },
+            TextColor(color),
+        )
+    }
+
+    pub fn value(&self, text: impl Into<String>, color: Color) -> impl Bundle {
+        (
+            TextSpan::new(text),
+            TextFont {
+                font_size: self.font_size,
+                ..default()
+            },
+            TextColor(color),
+        )
+    }
+}
+
+// Makes the style of the scoreboard available to the systems that spawn its lines
+pub struct ScoreboardPlugin {
+    pub style: ScoreboardStyle,
+}
+
+impl Plugin for ScoreboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.style.clone());
+    }
+}
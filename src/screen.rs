@@ -0,0 +1,126 @@
+// Overlays covering the whole window, like menus and game over screens.
+//
+// A screen is a title with some lines of text below it, scoped to a state so it is
+// despawned when the game leaves that state.
+
+use crate::scoreboard::ScoreboardStyle;
+use bevy::prelude::*;
+
+#[derive(Resource, Clone, Debug)]
+pub struct ScreenStyle {
+    pub title_font_size: f32,
+    pub line_font_size: f32,
+    pub row_gap: Val,
+    pub overlay_color: Color,
+    pub title_color: Color,
+    pub text_color: Color,
+}
+
+impl ScreenStyle {
+    // Spawns a centered overlay with a title and the lines below it, and returns the
+    // entities of the lines
+    pub fn spawn<S: States>(
+        &self,
+        commands: &mut Commands,
+        state: S,
+        title: &str,
+        lines: &[String],
+    ) -> Vec<Entity> {
+        let mut line_entities = Vec::with_capacity(lines.len());
+
+        commands
+            .spawn((
+                Node {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: self.row_gap,
+                    ..default()
+                },
+                BackgroundColor(self.overlay_color),
+                StateScoped(state),
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    Text::new(title),
+                    TextFont {
+                        font_size: self.title_font_size,
+                        ..default()
+                    },
+                    TextColor(self.title_color),
+                ));
+
+                for line in lines {
+                    let line_entity = parent
+                        .spawn((
+                            Text::new(line.clone()),
+                            TextFont {
+                                font_size: self.line_font_size,
+                                ..default()
+                            },
+                            TextColor(self.text_color),
+                        ))
+                        .id();
+                    line_entities.push(line_entity);
+                }
+            });
+
+        line_entities
+    }
+}
+
+// The colours of the screens and the scoreboard, which the games take from their configs
+#[derive(Clone, Copy, Debug)]
+pub struct TextColors {
+    pub overlay: Color,
+    pub title: Color,
+    pub text: Color,
+    pub value: Color,
+}
+
+// Gives the screens and the scoreboard new colours, and repaints the text and the overlays
+// already up in the old ones. Sizes and colours come from the games' configs, so their restyle
+// systems call this to change what's already there when the config is reloaded.
+pub fn restyle_text(
+    colors: TextColors,
+    screen_style: &mut ScreenStyle,
+    scoreboard_style: &mut ScoreboardStyle,
+    text_query: &mut Query<&mut TextColor>,
+    background_query: &mut Query<&mut BackgroundColor>,
+) {
+    let repaints = [
+        (screen_style.title_color, colors.title),
+        (screen_style.text_color, colors.text),
+        (scoreboard_style.text_color, colors.text),
+        (scoreboard_style.value_color, colors.value),
+    ];
+    for mut text_color in text_query.iter_mut() {
+        if let Some((_, new)) = repaints.iter().find(|(old, _)| text_color.0 == *old) {
+            text_color.set_if_neq(TextColor(*new));
+        }
+    }
+    for mut background in background_query.iter_mut() {
+        if background.0 == screen_style.overlay_color {
+            background.set_if_neq(BackgroundColor(colors.overlay));
+        }
+    }
+
+    screen_style.overlay_color = colors.overlay;
+    screen_style.title_color = colors.title;
+    screen_style.text_color = colors.text;
+    scoreboard_style.text_color = colors.text;
+    scoreboard_style.value_color = colors.value;
+}
+
+// Makes the style of the screens available to the systems that spawn them
+pub struct ScreenPlugin {
+    pub style: ScreenStyle,
+}
+
+impl Plugin for ScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.style.clone());
+    }
+}
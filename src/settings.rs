@@ -0,0 +1,233 @@
+// The controls screen shared by the games, where the player rebinds their actions.
+//
+// The screen lists the controls a game uses and what they're bound to. Confirm rebinds the
+// selected one to the next inputs pressed, Back cancels a rebind under way or leaves the screen
+// for the state it was opened from, and the bindings are saved on the way out.
+
+use crate::input::*;
+use crate::screen::ScreenStyle;
+use bevy::prelude::*;
+use bevy::state::state::FreelyMutableState;
+
+// How far the thrust controls must be pushed to move through the screen,
+// and a stick to be taken as its new binding
+pub const NAVIGATION_THRESHOLD: f32 = 0.5;
+// The screen has more lines than the others, so they're smaller and closer together
+const LINE_FONT_SCALE: f32 = 0.6;
+const ROW_GAP: Val = Val::Px(6.0);
+
+// The directions of an axis action, in the order their keys are rebound
+const DIRECTIONS: [&str; 4] = ["up", "down", "left", "right"];
+
+// A line of the settings screen, and the bindings it changes
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Control {
+    Button(ButtonAction),
+    // The direction keys of an axis action, rebound one after the other
+    Keys(AxisAction),
+    Stick(AxisAction),
+}
+
+impl Control {
+    pub fn name(&self) -> String {
+        match self {
+            Control::Button(action) => action.name().to_string(),
+            Control::Keys(action) => format!("{} keys", action.name()),
+            Control::Stick(action) => format!("{} stick", action.name()),
+        }
+    }
+
+    pub fn describe(&self, bindings: &InputBindings) -> String {
+        match *self {
+            Control::Button(action) => bindings.describe(action),
+            Control::Keys(action) => bindings.describe_axis(action, false),
+            Control::Stick(action) => bindings.describe_axis(action, true),
+        }
+    }
+}
+
+// The controls the settings screen lists, in order
+#[derive(Resource)]
+pub struct SettingsControls {
+    pub a: &'static [Control],
+}
+
+// Control selected on the settings screen, whether we're waiting for its new binding,
+// and the state Back leaves the screen for
+#[derive(Resource)]
+pub struct SettingsCursor<S: FreelyMutableState> {
+    pub selected: usize,
+    pub rebinding: bool,
+    // The keys pressed so far while rebinding the direction keys of an axis action
+    pub keys: Vec<KeyCode>,
+    pub back_to: S,
+}
+
+// Line of the settings screen showing the bindings of `SettingsControls.a[.0]`
+#[derive(Component)]
+pub struct SettingsRow(pub usize);
+
+pub fn spawn_settings_screen<S: FreelyMutableState>(
+    mut commands: Commands,
+    mut transitions: EventReader<StateTransitionEvent<S>>,
+    state: Res<State<S>>,
+    style: Res<ScreenStyle>,
+    controls: Res<SettingsControls>,
+    bindings: Res<InputBindings>,
+) {
+    let back_to = transitions
+        .read()
+        .last()
+        .and_then(|transition| transition.exited.clone())
+        .unwrap_or_else(|| state.get().clone());
+    commands.insert_resource(SettingsCursor {
+        selected: 0,
+        rebinding: false,
+        keys: Vec::new(),
+        back_to,
+    });
+
+    let mut lines: Vec<String> = controls.a.iter().map(Control::name).collect();
+    lines.push(format!(
+        "Move with the thrust controls, press {} to rebind, {} to cancel or go back",
+        bindings.describe(ButtonAction::Confirm),
+        bindings.describe(ButtonAction::Back),
+    ));
+
+    let style = ScreenStyle {
+        line_font_size: style.line_font_size * LINE_FONT_SCALE,
+        row_gap: ROW_GAP,
+        ..style.clone()
+    };
+    let line_entities = style.spawn(&mut commands, state.get().clone(), "Controls", &lines);
+
+    for (row, line_entity) in line_entities.into_iter().take(controls.a.len()).enumerate() {
+        commands.entity(line_entity).insert(SettingsRow(row));
+    }
+}
+
+// Moves through the controls with the thrust controls, and rebinds the selected one to the next
+// inputs pressed. Back cancels a rebind, so it is never taken as the new binding.
+pub fn settings_input<S: FreelyMutableState>(
+    mut input: ParamSet<(ActionInput, ResMut<InputBindings>)>,
+    controls: Res<SettingsControls>,
+    mut cursor: ResMut<SettingsCursor<S>>,
+    mut next_state: ResMut<NextState<S>>,
+    mut was_navigating: Local<bool>,
+) {
+    if cursor.rebinding {
+        if input.p0().just_pressed(ButtonAction::Back) {
+            cursor.rebinding = false;
+            cursor.keys.clear();
+            return;
+        }
+
+        match controls.a[cursor.selected] {
+            Control::Button(action) => {
+                let Some(binding) = input.p0().captured_binding() else {
+                    return;
+                };
+                input.p1().rebind(action, binding);
+            }
+            Control::Keys(action) => {
+                let Some(key) = input.p0().captured_key() else {
+                    return;
+                };
+                cursor.keys.push(key);
+                let [up, down, left, right] = cursor.keys[..] else {
+                    return;
+                };
+                input.p1().rebind_axis(
+                    action,
+                    AxisBinding::Keys {
+                        up,
+                        down,
+                        left,
+                        right,
+                    },
+                );
+            }
+            Control::Stick(action) => {
+                let Some(stick) = input.p0().captured_stick(NAVIGATION_THRESHOLD) else {
+                    return;
+                };
+                input.p1().rebind_axis(action, stick);
+            }
+        }
+        cursor.rebinding = false;
+        cursor.keys.clear();
+        return;
+    }
+
+    let action_input = input.p0();
+
+    if action_input.just_pressed(ButtonAction::Back) {
+        next_state.set(cursor.back_to.clone());
+    } else if action_input.just_pressed(ButtonAction::Confirm) {
+        cursor.rebinding = true;
+    }
+
+    // The thrust controls are an axis, so only the moment they're pushed moves the cursor
+    let vertical = action_input.axis(AxisAction::Thrust).y;
+    let navigating = vertical.abs() > NAVIGATION_THRESHOLD;
+
+    if navigating && !*was_navigating {
+        let rows = controls.a.len();
+        cursor.selected = if vertical > 0.0 {
+            (cursor.selected + rows - 1) % rows
+        } else {
+            (cursor.selected + 1) % rows
+        };
+    }
+    *was_navigating = navigating;
+}
+
+pub fn update_settings_screen<S: FreelyMutableState>(
+    style: Res<ScreenStyle>,
+    bindings: Res<InputBindings>,
+    controls: Res<SettingsControls>,
+    cursor: Res<SettingsCursor<S>>,
+    mut q_row: Query<(&SettingsRow, &mut Text, &mut TextColor)>,
+) {
+    for (row, mut text, mut text_color) in &mut q_row {
+        let control = controls.a[row.0];
+        let selected = row.0 == cursor.selected;
+
+        text.0 = if selected && cursor.rebinding {
+            let prompt = match control {
+                Control::Button(_) => "press a key or button".to_string(),
+                Control::Keys(_) => format!("press the {} key", DIRECTIONS[cursor.keys.len()]),
+                Control::Stick(_) => "push a stick".to_string(),
+            };
+            format!("{}: {prompt}", control.name())
+        } else {
+            format!("{}: {}", control.name(), control.describe(&bindings))
+        };
+        *text_color = TextColor(if selected {
+            style.title_color
+        } else {
+            style.text_color
+        });
+    }
+}
+
+// Shows the settings screen while the game is in `state`, listing `controls`.
+// The games open it by entering that state.
+pub struct SettingsPlugin<S> {
+    pub state: S,
+    pub controls: &'static [Control],
+}
+
+impl<S: FreelyMutableState> Plugin for SettingsPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SettingsControls { a: self.controls })
+            .add_systems(OnEnter(self.state.clone()), spawn_settings_screen::<S>)
+            .add_systems(OnExit(self.state.clone()), save_input_bindings)
+            .add_systems(
+                Update,
+                (settings_input::<S>, update_settings_screen::<S>)
+                    .chain()
+                    .run_if(in_state(self.state.clone())),
+            );
+    }
+}
@@ -0,0 +1,44 @@
+// Straight line motion, for games that don't need a physics engine.
+
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::prelude::*;
+
+// Units per second
+#[derive(Component, Deref, DerefMut, Default, Clone, Copy, Debug)]
+pub struct Velocity(pub Vec2);
+
+// Left alone by `integrate_velocity`, for entities that their own systems move,
+// like those swept along their path to stop where they hit something
+#[derive(Component, Default, Clone, Copy, Debug)]
+pub struct ManualMotion;
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntegrateVelocity;
+
+pub fn integrate_velocity(
+    time: Res<Time>,
+    mut query: Query<(&mut Transform, &Velocity), Without<ManualMotion>>,
+) {
+    for (mut transform, velocity) in &mut query {
+        transform.translation += velocity.extend(0.0) * time.delta_secs();
+    }
+}
+
+// Moves every entity with a `Velocity` but no `ManualMotion` in `schedule`, in the `IntegrateVelocity` set
+pub struct VelocityPlugin {
+    pub schedule: InternedScheduleLabel,
+}
+
+impl VelocityPlugin {
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        VelocityPlugin {
+            schedule: schedule.intern(),
+        }
+    }
+}
+
+impl Plugin for VelocityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(self.schedule, integrate_velocity.in_set(IntegrateVelocity));
+    }
+}